@@ -0,0 +1,177 @@
+//! Generates `memory.x` from the `flash-*` and `ram-*` features
+//!
+//! Only done with the `memory-x` feature, the MCU aliases (`py32f030xx8`, ...) then select one
+//! flash and one RAM size. Without it no linker script is generated, and the application provides
+//! its own `memory.x`.
+//!
+//! Optional regions are reserved with environment variables, sizes in KiB:
+//!
+//! - `PY32_BOOTLOADER_KB`: start of flash, the application is linked after it
+//! - `PY32_EEPROM_KB`: end of flash, for EEPROM emulation, exported as `_eeprom_start` and
+//!   `_eeprom_end`
+//! - `PY32_NOINIT_KB`: end of RAM, for the `.noinit` section, which is not cleared at reset
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const FLASH_ORIGIN: u32 = 0x0800_0000;
+const RAM_ORIGIN: u32 = 0x2000_0000;
+
+const FLASH_SIZES: &[u32] = &[16, 20, 24, 32, 48, 64, 96, 128];
+const RAM_SIZES: &[u32] = &[2, 3, 4, 6, 8, 12, 16];
+
+/// Flash sector size in KiB, the erase unit of EEPROM emulation
+const SECTOR_KB: u32 = 4;
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    for var in ["PY32_BOOTLOADER_KB", "PY32_EEPROM_KB", "PY32_NOINIT_KB"] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+
+    if env::var_os("CARGO_FEATURE_MEMORY_X").is_none() {
+        return;
+    }
+
+    let flash = selected("flash", FLASH_SIZES);
+    let ram = selected("ram", RAM_SIZES);
+    let (flash, ram) = match (flash, ram) {
+        (Some(flash), Some(ram)) => (flash, ram),
+        (None, None) => fail("the `memory-x` feature needs an MCU alias such as `py32f003xx4`"),
+        (Some(_), None) => fail("a `flash-*` feature is enabled without a `ram-*` feature"),
+        (None, Some(_)) => fail("a `ram-*` feature is enabled without a `flash-*` feature"),
+    };
+
+    let bootloader = size_var("PY32_BOOTLOADER_KB");
+    let eeprom = size_var("PY32_EEPROM_KB");
+    let noinit = size_var("PY32_NOINIT_KB");
+
+    if bootloader + eeprom >= flash {
+        fail(&format!(
+            "bootloader ({}K) and EEPROM ({}K) regions leave no room in {}K of flash",
+            bootloader, eeprom, flash
+        ));
+    }
+    if eeprom % SECTOR_KB != 0 {
+        fail(&format!(
+            "PY32_EEPROM_KB must be a multiple of the {}K flash sector",
+            SECTOR_KB
+        ));
+    }
+    if noinit >= ram {
+        fail(&format!(
+            "noinit region ({}K) leaves no room in {}K of RAM",
+            noinit, ram
+        ));
+    }
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(
+        out.join("memory.x"),
+        memory_x(flash, ram, bootloader, eeprom, noinit),
+    )
+    .unwrap();
+    println!("cargo:rustc-link-search={}", out.display());
+}
+
+/// Returns the size of the only enabled `<kind>-*` feature
+fn selected(kind: &str, sizes: &[u32]) -> Option<u32> {
+    let enabled: Vec<u32> = sizes
+        .iter()
+        .copied()
+        .filter(|size| {
+            env::var_os(format!("CARGO_FEATURE_{}_{}", kind.to_uppercase(), size)).is_some()
+        })
+        .collect();
+    match enabled[..] {
+        [] => None,
+        [size] => Some(size),
+        _ => {
+            let names: Vec<String> = enabled
+                .iter()
+                .map(|size| format!("`{}-{}`", kind, size))
+                .collect();
+            fail(&format!(
+                "conflicting {} size features {}, enable only one MCU alias",
+                kind,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+fn size_var(name: &str) -> u32 {
+    match env::var(name) {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .unwrap_or_else(|_| fail(&format!("{}={:?} is not a size in KiB", name, value))),
+        Err(_) => 0,
+    }
+}
+
+fn memory_x(flash: u32, ram: u32, bootloader: u32, eeprom: u32, noinit: u32) -> String {
+    let app_flash = flash - bootloader - eeprom;
+    let app_ram = ram - noinit;
+
+    let mut x = String::new();
+    x += "/* Generated by the py32f0xx-hal build script, do not edit */\n";
+    x += "MEMORY\n{\n";
+    x += "  /* NOTE K = KiBi = 1024 bytes */\n";
+    x += &format!("  /* {}K Flash, {}K RAM */\n", flash, ram);
+    if bootloader > 0 {
+        x += &format!(
+            "  BOOTLOADER : ORIGIN = 0x{:08X}, LENGTH = {}K\n",
+            FLASH_ORIGIN, bootloader
+        );
+    }
+    x += &format!(
+        "  FLASH : ORIGIN = 0x{:08X}, LENGTH = {}K\n",
+        FLASH_ORIGIN + bootloader * 1024,
+        app_flash
+    );
+    if eeprom > 0 {
+        x += &format!(
+            "  EEPROM : ORIGIN = 0x{:08X}, LENGTH = {}K\n",
+            FLASH_ORIGIN + (flash - eeprom) * 1024,
+            eeprom
+        );
+    }
+    x += &format!(
+        "  RAM : ORIGIN = 0x{:08X}, LENGTH = {}K\n",
+        RAM_ORIGIN, app_ram
+    );
+    if noinit > 0 {
+        x += &format!(
+            "  NOINIT : ORIGIN = 0x{:08X}, LENGTH = {}K\n",
+            RAM_ORIGIN + app_ram * 1024,
+            noinit
+        );
+    }
+    x += "}\n\n";
+
+    if eeprom > 0 {
+        x += "_eeprom_start = ORIGIN(EEPROM);\n";
+        x += "_eeprom_end = ORIGIN(EEPROM) + LENGTH(EEPROM);\n\n";
+    }
+    if noinit > 0 {
+        x += "SECTIONS\n{\n";
+        x += "  .noinit (NOLOAD) : ALIGN(4)\n  {\n";
+        x += "    *(.noinit .noinit.*);\n";
+        x += "    . = ALIGN(4);\n";
+        x += "  } > NOINIT\n";
+        x += "} INSERT AFTER .uninit;\n\n";
+    }
+
+    x += "/* This is where the call stack will be allocated. */\n";
+    x += "/* The stack is of the full descending type. */\n";
+    x += "/* NOTE Do NOT modify `_stack_start` unless you know what you are doing */\n";
+    x += "_stack_start = ORIGIN(RAM) + LENGTH(RAM);\n";
+    x
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("error: {}", message);
+    std::process::exit(1);
+}
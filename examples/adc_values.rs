@@ -52,7 +52,7 @@ fn main() -> ! {
         let _rx = gpioa.pa3.into_alternate_af1(); // don't need, can be removed
 
         // Initialise UART for transmission only
-        let mut tx = dp.USART1.tx(tx, 115_200.bps(), &rcc.clocks);
+        let mut tx = dp.USART1.tx(tx, 115_200.bps(), &rcc.clocks).unwrap();
 
         // Initialise ADC
         let adc = hal::adc::Adc::new(dp.ADC, hal::adc::AdcClockMode::default());
@@ -56,9 +56,10 @@ fn main() -> ! {
         #[allow(static_mut_refs)]
         let int_pin = unsafe { &mut *INT_PIN.as_mut_ptr() };
         *int_pin = gpioa.pa11.into_floating_input();
-        int_pin.make_interrupt_source(&mut p.EXTI);
-        int_pin.trigger_on_edge(&mut p.EXTI, Edge::RisingFalling);
-        int_pin.enable_interrupt(&mut p.EXTI);
+        let mut exti = p.EXTI.constrain();
+        let mut line = exti.gpio_line(int_pin).unwrap();
+        line.trigger_on_edge(Edge::RisingFalling);
+        line.enable_interrupt();
     } // initialization ends here
 
     unsafe {
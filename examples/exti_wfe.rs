@@ -0,0 +1,34 @@
+//! Sleeps with `WFE` until the pa11 pin is pulled low
+//!
+//! The EXTI line of pa11 is configured in event mode, so no interrupt handler is needed: the
+//! falling edge only wakes the core up. Each wakeup toggles the LED on pa12.
+
+#![no_main]
+#![no_std]
+
+use panic_halt as _;
+
+use py32f0xx_hal as hal;
+
+use crate::hal::{gpio::Edge, pac, prelude::*};
+use cortex_m_rt::entry;
+
+#[entry]
+fn main() -> ! {
+    let mut p = pac::Peripherals::take().unwrap();
+    let _rcc = p.RCC.configure().freeze(&mut p.FLASH);
+
+    let gpioa = p.GPIOA.split();
+    let mut led = gpioa.pa12.into_push_pull_output();
+    let button = gpioa.pa11.into_pull_up_input();
+
+    let mut exti = p.EXTI.constrain();
+    let mut line = exti.gpio_line(&button).unwrap();
+    line.trigger_on_edge(Edge::Falling);
+    line.enable_event();
+
+    loop {
+        cortex_m::asm::wfe();
+        led.toggle();
+    }
+}
@@ -29,7 +29,7 @@ fn main() -> ! {
     // USART2 for debug: PA0=TX, PA1=RX @ 9600bps
     let tx = gpioa.pa0.into_alternate_af9();
     let rx = gpioa.pa1.into_alternate_af9();
-    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks);
+    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks).unwrap();
 
     // Debug LED on PB5
     let mut debug_pin = gpiob.pb5.into_push_pull_output();
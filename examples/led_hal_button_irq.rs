@@ -29,7 +29,7 @@ static INT: Mutex<RefCell<Option<gpiob::PB2<Input<PullDown>>>>> = Mutex::new(Ref
 
 #[entry]
 fn main() -> ! {
-    let mut p = Peripherals::take().unwrap();
+    let p = Peripherals::take().unwrap();
     let cp = c_m_Peripherals::take().unwrap();
     // Enable clock for SYSCFG
     let rcc = p.RCC;
@@ -42,10 +42,11 @@ fn main() -> ! {
     let gpiob = p.GPIOB.split();
 
     // Configure PB2 as input (button)
-    let mut int_pin = gpiob.pb2.into_pull_down_input();
-    int_pin.make_interrupt_source(&mut p.EXTI);
-    int_pin.trigger_on_edge(&mut p.EXTI, Edge::Rising);
-    int_pin.enable_interrupt(&mut p.EXTI);
+    let int_pin = gpiob.pb2.into_pull_down_input();
+    let mut exti = p.EXTI.constrain();
+    let mut line = exti.gpio_line(&int_pin).unwrap();
+    line.trigger_on_edge(Edge::Rising);
+    line.enable_interrupt();
 
     // Configure PA5 as output (LED)
     let mut led = gpioa.pa5.into_push_pull_output();
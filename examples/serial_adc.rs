@@ -53,7 +53,7 @@ fn main() -> ! {
     let mut adc = Adc::new(p.ADC, AdcClockMode::default());
 
     // Configure USART2
-    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks);
+    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks).unwrap();
     
     // Startup messages
     serial.write_str("=== SERIAL ADC - PY32F003I DFN8 ===\r\n").ok();
@@ -0,0 +1,73 @@
+//! Line echo over interrupt driven USART1 on pa2 (TX) and pa3 (RX)
+//!
+//! Received bytes are collected until a carriage return and then sent back as one line, while
+//! the interrupt keeps receiving into the ring buffer.
+
+#![no_main]
+#![no_std]
+
+use panic_halt as _;
+
+use py32f0xx_hal as hal;
+
+use crate::hal::{
+    pac::{self, interrupt, Interrupt},
+    prelude::*,
+    serial::{BufferedInterrupt, BufferedSerial, RingBuffer},
+};
+
+use core::cell::RefCell;
+use cortex_m::interrupt::Mutex;
+use cortex_m_rt::entry;
+use embedded_io::Write;
+
+static IRQ: Mutex<RefCell<Option<BufferedInterrupt<pac::USART1>>>> = Mutex::new(RefCell::new(None));
+
+#[entry]
+fn main() -> ! {
+    let mut p = pac::Peripherals::take().unwrap();
+    let rcc = p.RCC.configure().sysclk(24.MHz()).freeze(&mut p.FLASH);
+
+    let gpioa = p.GPIOA.split();
+    let tx = gpioa.pa2.into_alternate_af1();
+    let rx = gpioa.pa3.into_alternate_af1();
+    let serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks).unwrap();
+
+    let rx_buffer = cortex_m::singleton!(: RingBuffer<64> = RingBuffer::new()).unwrap();
+    let tx_buffer = cortex_m::singleton!(: RingBuffer<128> = RingBuffer::new()).unwrap();
+    let (mut serial, irq) = BufferedSerial::new(serial, rx_buffer, tx_buffer);
+    cortex_m::interrupt::free(|cs| *IRQ.borrow(cs).borrow_mut() = Some(irq));
+    unsafe { cortex_m::peripheral::NVIC::unmask(Interrupt::USART1) };
+
+    serial.write_all(b"buffered echo\r\n").ok();
+
+    let mut line = [0u8; 64];
+    let mut len = 0;
+    loop {
+        let mut byte = [0u8];
+        match serial.read(&mut byte) {
+            Ok(_) => {
+                if byte[0] == b'\r' || len == line.len() {
+                    serial.write_all(&line[..len]).ok();
+                    serial.write_all(b"\r\n").ok();
+                    len = 0;
+                } else {
+                    line[len] = byte[0];
+                    len += 1;
+                }
+            }
+            Err(e) => {
+                writeln!(serial, "\r\nreceive error: {:?}\r", e).ok();
+            }
+        }
+    }
+}
+
+#[interrupt]
+fn USART1() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(irq) = IRQ.borrow(cs).borrow_mut().as_mut() {
+            irq.on_interrupt();
+        }
+    });
+}
@@ -44,7 +44,7 @@ fn main() -> ! {
     // Initialize debug pin LOW
     debug_pin.set_low();
 
-    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks);
+    let mut serial = p.USART2.serial((tx, rx), 9_600.bps(), &rcc.clocks).unwrap();
     serial.write_str("=== USART2 PA0/PA1 AF9 WORKING - 9600 bps ===\r\n").ok();
     serial.write_str("PA0: TX (AF9) - Register configured\r\n").ok();
     serial.write_str("PA1: RX (AF9) - Register configured\r\n").ok();
@@ -53,7 +53,7 @@ fn main() -> ! {
         &rcc.clocks,
     );
 
-    let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks);
+    let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks).unwrap();
 
     let mut datatx = [0];
     let datarx = [0];
@@ -86,7 +86,7 @@ fn main() -> ! {
     cortex_m::peripheral::NVIC::unpend(Interrupt::TIM16);
 
     // Set up our serial port
-    let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks);
+    let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks).unwrap();
 
     // Print a welcome message
     writeln!(
@@ -35,7 +35,7 @@ fn main() -> ! {
     let tx = gpioa.pa2.into_alternate_af1();
 
     // Obtain a serial peripheral with unidirectional communication
-    let mut serial = p.USART1.tx(tx, 115_200.bps(), &rcc.clocks);
+    let mut serial = p.USART1.tx(tx, 115_200.bps(), &rcc.clocks).unwrap();
 
     serial.write_str("RESET \r\n").ok();
 
@@ -56,7 +56,7 @@ use crate::{
             cfgr2::CKMODE_A,
             smpr::SMP_A,
         },
-        ADC, RCC,
+        ADC, GPIOA, GPIOB, RCC,
     },
     rcc::{Enable, Reset},
 };
@@ -272,6 +272,21 @@ adc_pins!(
     gpiob::PB1<Analog> => 9_u8,
 );
 
+// Dynamic pins must be switched to analog mode with `make_analog` before being sampled, reading
+// them in another mode fails with `PinModeError::IncorrectMode`
+adc_pins!(
+    gpioa::PA0<Dynamic> => 0_u8,
+    gpioa::PA1<Dynamic> => 1_u8,
+    gpioa::PA2<Dynamic> => 2_u8,
+    gpioa::PA3<Dynamic> => 3_u8,
+    gpioa::PA4<Dynamic> => 4_u8,
+    gpioa::PA5<Dynamic> => 5_u8,
+    gpioa::PA6<Dynamic> => 6_u8,
+    gpioa::PA7<Dynamic> => 7_u8,
+    gpiob::PB0<Dynamic> => 8_u8,
+    gpiob::PB1<Dynamic> => 9_u8,
+);
+
 #[derive(Debug, Default)]
 /// Internal temperature sensor (ADC Channel 11)
 pub struct VTemp;
@@ -561,6 +576,17 @@ impl Adc {
         }
     }
 
+    /// Returns true if the pin of channel `chan` is in analog mode, internal channels always are
+    fn is_analog(chan: u8) -> bool {
+        // NOTE(unsafe) atomic reads with no side effects
+        let (moder, pin) = match chan {
+            0..=7 => (unsafe { (*GPIOA::ptr()).moder.read().bits() }, chan),
+            8..=9 => (unsafe { (*GPIOB::ptr()).moder.read().bits() }, chan - 8),
+            _ => return true,
+        };
+        (moder >> (2 * pin)) & 0b11 == 0b11
+    }
+
     fn convert(&mut self) -> u16 {
         self.rb.cr.modify(|_, w| w.adstart().start_conversion());
         while self.rb.isr.read().eoc().is_not_complete() {}
@@ -579,9 +605,13 @@ where
     WORD: From<u16>,
     PIN: Channel<Adc, ID = u8>,
 {
-    type Error = ();
+    type Error = PinModeError;
 
     fn read(&mut self, _pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
+        // a dynamic pin may not be in analog mode
+        if !Self::is_analog(PIN::channel()) {
+            return Err(nb::Error::Other(PinModeError::IncorrectMode));
+        }
         self.power_up(PIN::channel());
         let res = self.convert();
         self.power_down();
@@ -0,0 +1,141 @@
+//! USART baud rate computation
+//!
+//! [BaudCalc] finds the `BRR` value closest to a requested baud rate with 16 times
+//! oversampling, and reports the baud rate actually achieved and its error. It has no hardware
+//! dependencies.
+//!
+//! ```rust
+//! let calc = BaudCalc::new(24_000_000, 115_200).unwrap();
+//! assert_eq!(calc.brr(), 208);
+//! assert_eq!(calc.achieved(), 115_385);
+//! assert_eq!(calc.error_ppm(), 1603);
+//! ```
+
+/// Smallest `BRR` value, a mantissa of 1
+pub const BRR_MIN: u32 = 16;
+
+/// Largest `BRR` value, 12 bits mantissa and 4 bits fraction
+pub const BRR_MAX: u32 = 0xffff;
+
+/// `BRR` value for a USART clock and baud rate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BaudCalc {
+    clock: u32,
+    requested: u32,
+    brr: u32,
+}
+
+impl BaudCalc {
+    /// Computes the `BRR` value for `baud` from the USART `clock`, rounded to the nearest value
+    ///
+    /// Returns `None` if `baud` is zero or out of the range of the divider.
+    pub const fn new(clock: u32, baud: u32) -> Option<Self> {
+        if baud == 0 {
+            return None;
+        }
+        let brr = (clock as u64 + baud as u64 / 2) / baud as u64;
+        if brr < BRR_MIN as u64 || brr > BRR_MAX as u64 {
+            return None;
+        }
+        Some(Self {
+            clock,
+            requested: baud,
+            brr: brr as u32,
+        })
+    }
+
+    /// The `BRR` register value
+    pub const fn brr(&self) -> u32 {
+        self.brr
+    }
+
+    /// The requested baud rate
+    pub const fn requested(&self) -> u32 {
+        self.requested
+    }
+
+    /// The baud rate achieved, rounded to the nearest integer
+    pub const fn achieved(&self) -> u32 {
+        ((self.clock as u64 + self.brr as u64 / 2) / self.brr as u64) as u32
+    }
+
+    /// Error of the achieved baud rate in parts per million of the requested one, positive if
+    /// it is faster
+    pub const fn error_ppm(&self) -> i32 {
+        // clock / brr - requested, scaled by 1e6 / requested
+        let diff = self.clock as i64 - self.requested as i64 * self.brr as i64;
+        let den = self.requested as i64 * self.brr as i64;
+        let scaled = diff * 1_000_000;
+        // round half away from zero
+        let error = if scaled >= 0 {
+            (scaled + den / 2) / den
+        } else {
+            (scaled - den / 2) / den
+        };
+        error as i32
+    }
+
+    /// Error of the achieved baud rate in percent of the requested one, positive if it is faster
+    pub fn error_percent(&self) -> f32 {
+        self.error_ppm() as f32 / 10_000.0
+    }
+
+    /// Returns true if the absolute error is at most `tolerance_ppm` parts per million
+    pub const fn is_within(&self, tolerance_ppm: u32) -> bool {
+        self.error_ppm().unsigned_abs() <= tolerance_ppm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn common_rates() {
+        // clock, baud, brr, achieved, error ppm
+        let table = [
+            (8_000_000, 9_600, 833, 9_604, 400),
+            (8_000_000, 115_200, 69, 115_942, 6_441),
+            (24_000_000, 9_600, 2_500, 9_600, 0),
+            (24_000_000, 115_200, 208, 115_385, 1_603),
+            (24_000_000, 1_000_000, 24, 1_000_000, 0),
+            (48_000_000, 9_600, 5_000, 9_600, 0),
+            (48_000_000, 115_200, 417, 115_108, -799),
+            (48_000_000, 1_000_000, 48, 1_000_000, 0),
+        ];
+        for (clock, baud, brr, achieved, error) in table {
+            let calc = BaudCalc::new(clock, baud).unwrap();
+            assert_eq!(calc.requested(), baud);
+            assert_eq!(calc.brr(), brr, "{clock} Hz, {baud} bps");
+            assert_eq!(calc.achieved(), achieved, "{clock} Hz, {baud} bps");
+            assert_eq!(calc.error_ppm(), error, "{clock} Hz, {baud} bps");
+        }
+    }
+
+    #[test]
+    fn tolerance() {
+        let calc = BaudCalc::new(8_000_000, 115_200).unwrap();
+        assert!(calc.is_within(6_441));
+        assert!(!calc.is_within(6_440));
+        let calc = BaudCalc::new(48_000_000, 115_200).unwrap();
+        assert!(calc.is_within(799));
+        assert!(!calc.is_within(798));
+        assert!(BaudCalc::new(24_000_000, 9_600).unwrap().is_within(0));
+    }
+
+    #[test]
+    fn zero_baud() {
+        assert_eq!(BaudCalc::new(24_000_000, 0), None);
+    }
+
+    #[test]
+    fn divider_range() {
+        // 1 Mbps needs a divider of 8 from 8 MHz, below the mantissa of 1
+        assert_eq!(BaudCalc::new(8_000_000, 1_000_000), None);
+        assert_eq!(BaudCalc::new(8_000_000, 500_000).unwrap().brr(), BRR_MIN);
+        assert_eq!(BaudCalc::new(48_000_000, 733).unwrap().brr(), 65_484);
+        assert_eq!(BaudCalc::new(48_000_000, 732), None);
+        assert_eq!(BaudCalc::new(u32::MAX, 1), None);
+    }
+}
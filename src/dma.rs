@@ -2,6 +2,7 @@
 #![allow(dead_code)]
 
 use crate::pac;
+use crate::waker::WakerSlot;
 use core::{
     convert::TryFrom,
     marker::PhantomData,
@@ -17,6 +18,8 @@ use embedded_dma::{ReadBuffer, WriteBuffer};
 pub enum Error {
     /// The event was not handled before succeeding event happened
     Overrun,
+    /// A bus error stopped the transfer
+    TransferError,
 }
 
 /// Event types for DMA peripheral
@@ -193,6 +196,29 @@ impl core::convert::From<PeriphMap> for u32 {
     }
 }
 
+/// Wakers of the tasks waiting on a channel, indexed by channel number - 1
+static WAKERS: [WakerSlot; 3] = [WakerSlot::new(), WakerSlot::new(), WakerSlot::new()];
+
+/// DMA interrupt handler for async transfers
+///
+/// Disables the interrupts of every channel with a pending enabled event and wakes the task
+/// waiting on it. The flags are left set for the task to inspect. Bind it to the `DMA_Channel1`
+/// and `DMA_Channel2_3` interrupts when async serial or SPI transfers use DMA.
+pub fn on_interrupt() {
+    // NOTE(unsafe) only the interrupt enable bits of channels with a pending event are modified
+    let dma = unsafe { &*pac::DMA::ptr() };
+    let isr = dma.isr.read().bits();
+    for (index, waker) in WAKERS.iter().enumerate() {
+        let ch = &dma.ch[index];
+        // TCIE, HTIE and TEIE have the bit positions of TCIF, HTIF and TEIF in the channel flags
+        let enabled = ch.cr.read().bits() & 0b1110;
+        if (isr >> (index * 4)) & enabled != 0 {
+            ch.cr.modify(|r, w| unsafe { w.bits(r.bits() & !0b1110) });
+            waker.wake();
+        }
+    }
+}
+
 /// Circular Buffer for DMA Transfers
 pub struct CircBuffer<BUFFER, PAYLOAD>
 where
@@ -330,6 +356,15 @@ impl<DMA: DmaExt, const C: u8> Ch<DMA, C> {
         self.ch().cr.modify(|_, w| w.pl().bits(prio_bits));
     }
 
+    /// 'size' sets the data size of both the peripheral and the memory
+    pub fn set_data_size(&mut self, size: DataSize) {
+        self.ch().cr.modify(|_, w| match size {
+            DataSize::Bits8 => w.msize().bits8().psize().bits8(),
+            DataSize::Bits16 => w.msize().bits16().psize().bits16(),
+            DataSize::Bits32 => w.msize().bits32().psize().bits32(),
+        });
+    }
+
     /// Number of bytes to transfer
     pub fn set_transfer_length(&mut self, len: usize) {
         self.ch()
@@ -376,6 +411,35 @@ impl<DMA: DmaExt, const C: u8> Ch<DMA, C> {
         }
     }
 
+    /// Enables the interrupt of `event` for an async transfer, see [on_interrupt]
+    pub(crate) fn listen_async(&mut self, event: Event) {
+        cortex_m::interrupt::free(|_| self.listen(event));
+    }
+
+    /// Enables the transfer error interrupt for an async transfer, see [on_interrupt]
+    pub(crate) fn listen_async_error(&mut self) {
+        cortex_m::interrupt::free(|_| self.ch().cr.modify(|_, w| w.teie().set_bit()));
+    }
+
+    /// Disables the interrupts enabled by [Ch::listen_async] and [Ch::listen_async_error]
+    pub(crate) fn unlisten_async(&mut self) {
+        cortex_m::interrupt::free(|_| {
+            self.ch()
+                .cr
+                .modify(|_, w| w.tcie().clear_bit().htie().clear_bit().teie().clear_bit())
+        });
+    }
+
+    /// Returns `true` if a bus error stopped the transfer
+    pub(crate) fn transfer_error(&self) -> bool {
+        unsafe { self.isr().teif(C).bit_is_set() }
+    }
+
+    /// Waker slot of the channel, woken by [on_interrupt]
+    pub(crate) fn waker(&self) -> &'static WakerSlot {
+        &WAKERS[C as usize - 1]
+    }
+
     /// Get the [RegisterBlock] for a DMA channel
     pub(crate) fn ch(&mut self) -> &pac::dma::CH {
         unsafe { &(*DMA::ptr()).ch[C as usize - 1] }
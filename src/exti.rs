@@ -0,0 +1,433 @@
+//! # Extended interrupt and event controller
+//!
+//! The EXTI peripheral routes GPIO pins and a few internal peripheral signals to the NVIC
+//! (interrupt mode) and to the core event input (event mode, used to wake from `WFE`).
+//!
+//! Lines 0 to 15 are shared by all GPIO ports: line `n` can only be connected to pin `n` of a
+//! single port at a time. Lines 16 and up are connected to internal peripherals.
+//!
+//! To use the EXTI, first constrain the `pac` peripheral, then claim the lines you need. Each claim
+//! hands out a token that is used to configure that line, and that can be moved into an interrupt
+//! handler independently of the others.
+//!
+//! ```rust
+//! let mut exti = dp.EXTI.constrain();
+//! let gpioa = dp.GPIOA.split();
+//! let button = gpioa.pa11.into_pull_up_input();
+//!
+//! let mut line = exti.gpio_line(&button).unwrap();
+//! line.trigger_on_edge(Edge::Falling);
+//! line.enable_interrupt();
+//!
+//! // claiming line 11 again, for example for PB11, now fails
+//! ```
+//!
+//! Lines that only need to wake the core from `WFE` should use [ExtiLine::enable_event] instead
+//! of [ExtiLine::enable_interrupt], in which case no interrupt handler is required.
+//!
+//! GPIO lines can also be awaited: wrap the pin and its line in an [ExtiInput], which implements
+//! `embedded_hal_async::digital::Wait`, and call [on_interrupt] from the EXTI interrupt handlers.
+//!
+//! The configurable lines (GPIO, PVD and comparators) are handed out as [ExtiLine], while the
+//! direct lines (RTC and LPTIM) which are always triggered by their peripheral's own
+//! interrupt flags, are handed out as [DirectLine].
+
+use crate::gpio::{Edge, ExtiPin, PinExt};
+use crate::pac::EXTI;
+
+mod hal_async;
+pub use hal_async::{on_interrupt, ExtiInput};
+
+/// Read-modify-write of an EXTI register shared between all line tokens
+///
+/// Tokens own distinct bits, the critical section makes the read-modify-write atomic with
+/// respect to other tokens used from interrupt handlers.
+macro_rules! modify {
+    ($reg:ident, $mask:expr, $set:expr) => {{
+        let mask: u32 = $mask;
+        let set: bool = $set;
+        cortex_m::interrupt::free(|_| {
+            let exti = unsafe { &*EXTI::ptr() };
+            exti.$reg.modify(|r, w| unsafe {
+                if set {
+                    w.bits(r.bits() | mask)
+                } else {
+                    w.bits(r.bits() & !mask)
+                }
+            });
+        });
+    }};
+}
+
+/// Number of EXTI lines shared between the GPIO ports
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002a",
+    feature = "py32f072"
+))]
+pub const GPIO_LINES: u8 = 16;
+/// Number of EXTI lines shared between the GPIO ports
+#[cfg(feature = "py32f002b")]
+pub const GPIO_LINES: u8 = 8;
+
+/// EXTI errors
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    /// The line has already been claimed, by this port or another one
+    LineInUse,
+    /// The pin has no EXTI line on this device
+    NoSuchLine,
+}
+
+/// Extension trait to constrain the EXTI peripheral
+pub trait ExtiExt {
+    /// Constrains the EXTI peripheral so lines can be claimed one by one
+    fn constrain(self) -> Exti;
+}
+
+impl ExtiExt for EXTI {
+    fn constrain(self) -> Exti {
+        Exti::new(self)
+    }
+}
+
+/// Constrained EXTI peripheral
+///
+/// Keeps track of which lines have been handed out, so a line shared between GPIO ports can
+/// only be owned by one of them.
+pub struct Exti {
+    exti: EXTI,
+    claimed: u32,
+}
+
+impl Exti {
+    /// Takes ownership of the EXTI peripheral, masks every line and clears pending flags
+    pub fn new(exti: EXTI) -> Self {
+        exti.imr.reset();
+        exti.emr.reset();
+        exti.rtsr.reset();
+        exti.ftsr.reset();
+        exti.pr.write(|w| unsafe { w.bits(0xffff_ffff) });
+        Self { exti, claimed: 0 }
+    }
+
+    /// Claims the line connected to `pin` and selects the pin's port on the line multiplexer
+    ///
+    /// Fails with [Error::LineInUse] if the line is already owned, whichever port owns it, and
+    /// with [Error::NoSuchLine] if the multiplexer cannot connect the pin's port to the line.
+    pub fn gpio_line<PIN>(&mut self, pin: &PIN) -> Result<ExtiLine, Error>
+    where
+        PIN: PinExt + ExtiPin,
+    {
+        let line = pin.pin_id();
+        if line >= GPIO_LINES {
+            return Err(Error::NoSuchLine);
+        }
+        if self.is_claimed(line) {
+            return Err(Error::LineInUse);
+        }
+        select_port(&self.exti, pin.port_id(), line)?;
+        self.claim(line)?;
+        Ok(ExtiLine { line })
+    }
+
+    /// Claims the line connected to the power voltage detector output
+    #[cfg(not(feature = "py32f002b"))]
+    pub fn pvd(&mut self) -> Result<ExtiLine, Error> {
+        self.claim(16).map(|_| ExtiLine { line: 16 })
+    }
+
+    /// Claims the line connected to the comparator 1 output
+    #[cfg(not(feature = "py32f002b"))]
+    pub fn comp1(&mut self) -> Result<ExtiLine, Error> {
+        self.claim(17).map(|_| ExtiLine { line: 17 })
+    }
+
+    /// Claims the line connected to the comparator 2 output
+    #[cfg(not(feature = "py32f002b"))]
+    pub fn comp2(&mut self) -> Result<ExtiLine, Error> {
+        self.claim(18).map(|_| ExtiLine { line: 18 })
+    }
+
+    /// Claims the line connected to the comparator 3 output
+    #[cfg(feature = "py32f072")]
+    pub fn comp3(&mut self) -> Result<ExtiLine, Error> {
+        self.claim(20).map(|_| ExtiLine { line: 20 })
+    }
+
+    /// Claims the line connected to the RTC alarm and second interrupts
+    #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
+    pub fn rtc(&mut self) -> Result<DirectLine, Error> {
+        self.claim(19).map(|_| DirectLine { line: 19 })
+    }
+
+    /// Claims the line connected to the low power timer interrupt
+    #[cfg(any(
+        feature = "py32f030",
+        feature = "py32f003",
+        feature = "py32f002a",
+        feature = "py32f002b",
+        feature = "py32f072"
+    ))]
+    pub fn lptim(&mut self) -> Result<DirectLine, Error> {
+        self.claim(29).map(|_| DirectLine { line: 29 })
+    }
+
+    /// Returns a configurable line to the controller, disabling it
+    pub fn release(&mut self, line: ExtiLine) {
+        let mask = 1 << line.line;
+        modify!(imr, mask, false);
+        modify!(emr, mask, false);
+        modify!(rtsr, mask, false);
+        modify!(ftsr, mask, false);
+        self.exti.pr.write(|w| unsafe { w.bits(mask) });
+        self.claimed &= !mask;
+    }
+
+    /// Returns a direct line to the controller, disabling it
+    pub fn release_direct(&mut self, line: DirectLine) {
+        let mask = 1 << line.line;
+        modify!(imr, mask, false);
+        modify!(emr, mask, false);
+        self.claimed &= !mask;
+    }
+
+    /// Returns true if `line` has been claimed
+    pub fn is_claimed(&self, line: u8) -> bool {
+        self.claimed & (1 << line) != 0
+    }
+
+    /// Releases the EXTI peripheral
+    ///
+    /// Lines that are still claimed keep their configuration.
+    pub fn free(self) -> EXTI {
+        self.exti
+    }
+
+    fn claim(&mut self, line: u8) -> Result<(), Error> {
+        if self.is_claimed(line) {
+            return Err(Error::LineInUse);
+        }
+        self.claimed |= 1 << line;
+        Ok(())
+    }
+}
+
+/// Token for a configurable EXTI line (GPIO, PVD or comparator)
+pub struct ExtiLine {
+    line: u8,
+}
+
+/// Token for a direct EXTI line (RTC or LPTIM)
+///
+/// Direct lines have no edge detector and no software trigger, they follow the interrupt flag of
+/// their peripheral.
+pub struct DirectLine {
+    line: u8,
+}
+
+macro_rules! line_common {
+    ($LINE:ident) => {
+        impl $LINE {
+            /// Returns the EXTI line number
+            #[inline(always)]
+            pub fn line(&self) -> u8 {
+                self.line
+            }
+
+            /// Enable the interrupt request for this line
+            pub fn enable_interrupt(&mut self) {
+                modify!(imr, 1 << self.line, true);
+            }
+
+            /// Disable the interrupt request for this line
+            pub fn disable_interrupt(&mut self) {
+                modify!(imr, 1 << self.line, false);
+            }
+
+            /// Enable the event request for this line, waking the core from `WFE`
+            pub fn enable_event(&mut self) {
+                modify!(emr, 1 << self.line, true);
+            }
+
+            /// Disable the event request for this line
+            pub fn disable_event(&mut self) {
+                modify!(emr, 1 << self.line, false);
+            }
+        }
+    };
+}
+
+line_common!(ExtiLine);
+line_common!(DirectLine);
+
+impl ExtiLine {
+    /// Generate requests on rising edge, falling edge or both
+    pub fn trigger_on_edge(&mut self, edge: Edge) {
+        let mask = 1 << self.line;
+        let (rising, falling) = match edge {
+            Edge::Rising => (true, false),
+            Edge::Falling => (false, true),
+            Edge::RisingFalling => (true, true),
+        };
+        modify!(rtsr, mask, rising);
+        modify!(ftsr, mask, falling);
+    }
+
+    /// Trigger the line from software
+    ///
+    /// The request is raised on the line as if an edge had been detected, if interrupt or
+    /// event mode is enabled.
+    pub fn trigger(&mut self) {
+        modify!(swier, 1 << self.line, true);
+    }
+
+    /// Returns true if the line has a pending request
+    pub fn is_pending(&self) -> bool {
+        // NOTE(unsafe) atomic read with no side effects
+        unsafe { (*EXTI::ptr()).pr.read().bits() & (1 << self.line) != 0 }
+    }
+
+    /// Clear the pending bit of this line
+    pub fn clear_pending(&mut self) {
+        // NOTE(unsafe) write 1 to clear, other lines are not affected
+        unsafe { (*EXTI::ptr()).pr.write(|w| w.bits(1 << self.line)) };
+    }
+}
+
+/// Connect EXTI line `pin` to GPIO port `port_id`
+///
+/// `port_id` is the ascii port name, as returned by [PinExt::port_id]. Fails with
+/// [Error::NoSuchLine] if the multiplexer cannot connect the port to the line.
+#[cfg(not(feature = "py32f072"))]
+pub(crate) fn select_port(
+    exti: &crate::pac::exti::RegisterBlock,
+    port_id: u8,
+    pin: u8,
+) -> Result<(), Error> {
+    let offset = 8 * (pin % 4);
+    // for pins 0-3, mux selects ports a=0, b=1, or f=2
+    // for py32f002b, a=0, b=1, c=2
+    // pins 4-8 only select between a=0 and b=1
+    let port = match port_id {
+        b'A' => 0,
+        b'B' => 1,
+        b'C' | b'F' => 2,
+        _ => return Err(Error::NoSuchLine),
+    };
+    let mask = 0xf << offset;
+    match pin {
+        0..=3 => {
+            exti.exticr1
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        4..=7 => {
+            exti.exticr2
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+        8 => {
+            exti.exticr3
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        // pins 9-15, only port A, no mux
+        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+        9..=15 if port == 0 => {}
+        _ => return Err(Error::NoSuchLine),
+    }
+    Ok(())
+}
+
+/// Returns true if EXTI line `pin` is connected to GPIO port `port_id`
+#[cfg(not(feature = "py32f072"))]
+pub(crate) fn is_port_selected(
+    exti: &crate::pac::exti::RegisterBlock,
+    port_id: u8,
+    pin: u8,
+) -> bool {
+    let port = match port_id {
+        b'A' => 0,
+        b'B' => 1,
+        b'C' | b'F' => 2,
+        _ => return false,
+    };
+    let bits = match pin {
+        0..=3 => exti.exticr1.read().bits(),
+        4..=7 => exti.exticr2.read().bits(),
+        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+        8 => exti.exticr3.read().bits(),
+        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+        9..=15 => return port == 0,
+        _ => return false,
+    };
+    (bits >> (8 * (pin % 4))) & 0xf == port
+}
+
+/// Connect EXTI line `pin` to GPIO port `port_id`
+///
+/// `port_id` is the ascii port name, as returned by [PinExt::port_id]. Fails with
+/// [Error::NoSuchLine] if the multiplexer cannot connect the port to the line.
+#[cfg(feature = "py32f072")]
+pub(crate) fn select_port(
+    exti: &crate::pac::exti::RegisterBlock,
+    port_id: u8,
+    pin: u8,
+) -> Result<(), Error> {
+    let offset = 8 * (pin % 4);
+    // every line selects between ports a=0, b=1, c=2 and f=3
+    let port = match port_id {
+        b'A' => 0,
+        b'B' => 1,
+        b'C' => 2,
+        b'F' => 3,
+        _ => return Err(Error::NoSuchLine),
+    };
+    let mask = 0xf << offset;
+    match pin {
+        0..=3 => {
+            exti.exticr1
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        4..=7 => {
+            exti.exticr2
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        8..=11 => {
+            exti.exticr3
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        12..=15 => {
+            exti.exticr4
+                .modify(|r, w| unsafe { w.bits((r.bits() & !mask) | (port << offset)) });
+        }
+        _ => return Err(Error::NoSuchLine),
+    }
+    Ok(())
+}
+
+/// Returns true if EXTI line `pin` is connected to GPIO port `port_id`
+#[cfg(feature = "py32f072")]
+pub(crate) fn is_port_selected(
+    exti: &crate::pac::exti::RegisterBlock,
+    port_id: u8,
+    pin: u8,
+) -> bool {
+    let port = match port_id {
+        b'A' => 0,
+        b'B' => 1,
+        b'C' => 2,
+        b'F' => 3,
+        _ => return false,
+    };
+    let bits = match pin {
+        0..=3 => exti.exticr1.read().bits(),
+        4..=7 => exti.exticr2.read().bits(),
+        8..=11 => exti.exticr3.read().bits(),
+        12..=15 => exti.exticr4.read().bits(),
+        _ => return false,
+    };
+    (bits >> (8 * (pin % 4))) & 0xf == port
+}
@@ -0,0 +1,224 @@
+//! Async edge waiting on GPIO lines
+//!
+//! Every GPIO line has a waker slot. A waiting future arms its line (edge selection and
+//! interrupt mask), and [on_interrupt] disarms the line again and wakes the task when the edge
+//! is seen. The application binds [on_interrupt] to the `EXTI0_1`, `EXTI2_3` and `EXTI4_15`
+//! interrupts:
+//!
+//! ```rust
+//! #[interrupt]
+//! fn EXTI4_15() {
+//!     py32f0xx_hal::exti::on_interrupt();
+//! }
+//! ```
+
+use super::{is_port_selected, ExtiLine, GPIO_LINES};
+use crate::gpio::{Edge, ExtiPin, PinExt};
+use crate::pac::EXTI;
+
+use core::cell::{Cell, RefCell};
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, Waker};
+
+use cortex_m::interrupt::Mutex;
+use embedded_hal::digital::{ErrorType, InputPin};
+
+#[allow(clippy::declare_interior_mutable_const)]
+const NO_WAKER: Mutex<RefCell<Option<Waker>>> = Mutex::new(RefCell::new(None));
+static WAKERS: [Mutex<RefCell<Option<Waker>>>; GPIO_LINES as usize] =
+    [NO_WAKER; GPIO_LINES as usize];
+/// Lines armed by a waiting future, one bit per line
+static ARMED: Mutex<Cell<u32>> = Mutex::new(Cell::new(0));
+
+/// EXTI interrupt handler for [ExtiInput] lines
+///
+/// Disarms every line that has a pending request and a waiting future, clears its pending bit
+/// and wakes the task. Other lines are left untouched, so they can still be serviced by the
+/// application in the same handler.
+pub fn on_interrupt() {
+    cortex_m::interrupt::free(|cs| {
+        // NOTE(unsafe) only armed lines are modified, and those are owned by the waiting futures
+        let exti = unsafe { &*EXTI::ptr() };
+        let armed = ARMED.borrow(cs);
+        let fired = exti.pr.read().bits() & armed.get();
+        if fired == 0 {
+            return;
+        }
+        exti.imr.modify(|r, w| unsafe { w.bits(r.bits() & !fired) });
+        exti.pr.write(|w| unsafe { w.bits(fired) });
+        armed.set(armed.get() & !fired);
+        for (line, waker) in WAKERS.iter().enumerate() {
+            if fired & (1 << line) != 0 {
+                if let Some(waker) = waker.borrow(cs).borrow_mut().take() {
+                    waker.wake();
+                }
+            }
+        }
+    });
+}
+
+/// Input pin that can wait for edges and levels asynchronously
+///
+/// Owns the pin and the [ExtiLine] token of its line, so no other port can use the line
+/// meanwhile.
+pub struct ExtiInput<PIN> {
+    pin: PIN,
+    line: ExtiLine,
+}
+
+impl<PIN> ExtiInput<PIN>
+where
+    PIN: PinExt + ExtiPin + InputPin,
+{
+    /// Create an async input from a pin and the line claimed for it
+    ///
+    /// # Panics
+    ///
+    /// Panics if `line` is not the line of `pin`, or if the line is connected to another port.
+    pub fn new(pin: PIN, line: ExtiLine) -> Self {
+        assert_eq!(
+            pin.pin_id(),
+            line.line(),
+            "EXTI line does not match the pin"
+        );
+        // NOTE(unsafe) atomic read with no side effects
+        let exti = unsafe { &*EXTI::ptr() };
+        assert!(
+            is_port_selected(exti, pin.port_id(), pin.pin_id()),
+            "EXTI line is connected to another port"
+        );
+        Self { pin, line }
+    }
+
+    /// Releases the pin and its line token
+    pub fn release(self) -> (PIN, ExtiLine) {
+        (self.pin, self.line)
+    }
+
+    async fn wait_for(&mut self, edge: Edge, level: Option<bool>) -> Result<(), PIN::Error> {
+        let mut future = ExtiFuture::new(&mut self.line, edge);
+        // arm before sampling the level, so an edge in between is not missed
+        future.arm();
+        if let Some(high) = level {
+            if self.pin.is_high()? == high {
+                return Ok(());
+            }
+        }
+        future.await;
+        Ok(())
+    }
+}
+
+impl<PIN> ErrorType for ExtiInput<PIN>
+where
+    PIN: ErrorType,
+{
+    type Error = PIN::Error;
+}
+
+impl<PIN> InputPin for ExtiInput<PIN>
+where
+    PIN: PinExt + ExtiPin + InputPin,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_high()
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        self.pin.is_low()
+    }
+}
+
+impl<PIN> embedded_hal_async::digital::Wait for ExtiInput<PIN>
+where
+    PIN: PinExt + ExtiPin + InputPin,
+{
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Edge::Rising, Some(true)).await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Edge::Falling, Some(false)).await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Edge::Rising, None).await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Edge::Falling, None).await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        self.wait_for(Edge::RisingFalling, None).await
+    }
+}
+
+/// Future completing when [on_interrupt] disarms its line
+struct ExtiFuture<'a> {
+    line: &'a mut ExtiLine,
+    edge: Edge,
+    armed: bool,
+}
+
+impl<'a> ExtiFuture<'a> {
+    fn new(line: &'a mut ExtiLine, edge: Edge) -> Self {
+        Self {
+            line,
+            edge,
+            armed: false,
+        }
+    }
+
+    fn arm(&mut self) {
+        let mask = 1 << self.line.line();
+        cortex_m::interrupt::free(|cs| {
+            self.line.disable_interrupt();
+            self.line.trigger_on_edge(self.edge);
+            self.line.clear_pending();
+            let armed = ARMED.borrow(cs);
+            armed.set(armed.get() | mask);
+            self.line.enable_interrupt();
+        });
+        self.armed = true;
+    }
+}
+
+impl Future for ExtiFuture<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if !self.armed {
+            self.arm();
+        }
+        let index = self.line.line() as usize;
+        cortex_m::interrupt::free(|cs| {
+            if ARMED.borrow(cs).get() & (1 << index) == 0 {
+                self.armed = false;
+                return Poll::Ready(());
+            }
+            let mut waker = WAKERS[index].borrow(cs).borrow_mut();
+            match waker.as_ref() {
+                Some(w) if w.will_wake(cx.waker()) => {}
+                _ => *waker = Some(cx.waker().clone()),
+            }
+            Poll::Pending
+        })
+    }
+}
+
+impl Drop for ExtiFuture<'_> {
+    fn drop(&mut self) {
+        if self.armed {
+            let mask = 1 << self.line.line();
+            let index = self.line.line() as usize;
+            cortex_m::interrupt::free(|cs| {
+                self.line.disable_interrupt();
+                let armed = ARMED.borrow(cs);
+                armed.set(armed.get() & !mask);
+                WAKERS[index].borrow(cs).borrow_mut().take();
+            });
+        }
+    }
+}
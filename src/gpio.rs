@@ -54,7 +54,9 @@
 //! the risk of runtime errors.
 //!
 //! To make a pin dynamic, use the `into_dynamic` function, and then use the `make_<mode>` functions to
-//! change the mode
+//! change the mode. Besides inputs and outputs, a dynamic pin can be switched to analog mode with
+//! `make_analog`, and to any alternate function with `make_alternate`, which takes an [AfConfig]
+//! with the pull, speed and output stage to use. Dynamic pins keep this ability once erased.
 //!
 //! ## Accessing PA13, and PA14
 //!
@@ -206,6 +208,28 @@ pub struct AF15;
 /// Digital output pin state
 pub use embedded_hal_02::digital::v2::PinState;
 
+/// Implements peripheral pin marker traits for alternate function pins
+///
+/// `INSTANCE => { Trait => [PA0<AF1>, ...], }` implements `Trait<pac::INSTANCE>` for
+/// `PA0<Alternate<AF1>>`. The invocations are generated by `tools/afgen.py`.
+macro_rules! af_pins {
+    ($($INST:ident => {
+        $($TRAIT:ident => [$($PIN:ident<$AF:ident>),* $(,)?],)+
+    })+) => {
+        $(
+            $(
+                $(
+                    impl $TRAIT<$crate::pac::$INST>
+                        for $crate::gpio::$PIN<$crate::gpio::Alternate<$crate::gpio::$AF>>
+                    {
+                    }
+                )*
+            )+
+        )+
+    };
+}
+pub(crate) use af_pins;
+
 /// Specify what type of edge transition will cause an interrupt
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Edge {
@@ -243,8 +267,19 @@ impl<MODE> Interruptable for Input<MODE> {}
 impl Interruptable for Dynamic {}
 
 /// Extension trait for an Interrupt Pin
+///
+/// See [Exti](crate::exti::Exti) for line ownership tracking, event mode and software triggers.
 pub trait ExtiPin {
     /// Make a pin an interrupt source
+    ///
+    /// The line is not tracked by [Exti](crate::exti::Exti), which owns the `EXTI` peripheral
+    /// once constrained, so the two cannot be mixed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line is enabled for a pin of another port, or if the pin cannot be connected
+    /// to its line.
+    #[deprecated(note = "use `Exti::gpio_line`, which tracks line ownership and reports errors")]
     fn make_interrupt_source(&mut self, exti: &mut pac::EXTI);
     /// Set the [Edge] trigger on a pin
     fn trigger_on_edge(&mut self, exti: &mut pac::EXTI, level: Edge);
@@ -266,49 +301,14 @@ where
 {
     /// Make corresponding EXTI line sensitive to this pin
     fn make_interrupt_source(&mut self, exti: &mut pac::EXTI) {
-        let pin_number = self.pin_id();
-        let offset = 8 * (pin_number % 4);
-        // for pins 0-3, mux selects ports a=0, b=1, or f=2
-        // for py32f002b, a=0, b=1, c=2
-        let port = match self.port_id() {
-            b'A' => 0,
-            b'B' => 1,
-            b'C' | b'F' => 2,
-            _ => unreachable!(),
-        };
-        // note(Safety) While the match below has invalid combinations for some parts, it cannot
-        // actually hit those, because the match works off the pin and port number which is embedded
-        // in the pin as states, so they can't have an invalid combination
-        // For example there only 2 pins on port C of the py32f002b
-        match pin_number {
-            // pins 0-3, mux selects port as above
-            0..=3 => {
-                exti.exticr1.modify(|r, w| unsafe {
-                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
-                });
-            }
-            // pin 4 mux selects port
-            4 => {
-                exti.exticr2.modify(|r, w| unsafe {
-                    w.bits((r.bits() & !(0xf << offset)) | (port << offset))
-                });
-            }
-            // pins 5-7, mux selects ports a=0, b=1
-            5..=7 => {
-                exti.exticr2
-                    .modify(|r, w| unsafe { w.bits(r.bits() | (port << offset)) });
-            }
-            // pin 8, mux selects ports a=0, b=1
-            #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
-            8 => {
-                exti.exticr3
-                    .modify(|r, w| unsafe { w.bits(r.bits() | (port << offset)) });
-            }
-            // pin 9-15, no mux
-            #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
-            9..=15 => {}
-            _ => unreachable!(),
-        }
+        let (port, line) = (self.port_id(), self.pin_id());
+        let enabled = (exti.imr.read().bits() | exti.emr.read().bits()) & (1 << line) != 0;
+        assert!(
+            !enabled || crate::exti::is_port_selected(exti, port, line),
+            "EXTI line is used by another port"
+        );
+        crate::exti::select_port(exti, port, line)
+            .expect("the pin cannot be connected to its EXTI line");
     }
 
     /// Generate interrupt on rising edge, falling edge or both
@@ -359,7 +359,80 @@ where
     }
 }
 
+/// Pull up/down resistor configuration
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Pull {
+    /// No pull resistor
+    #[default]
+    None,
+    /// Weak pull-up resistor
+    Up,
+    /// Weak pull-down resistor
+    Down,
+}
+
+/// Output speed (slew rate) of a pin
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Speed {
+    /// Very low speed
+    #[default]
+    VeryLow = 0b00,
+    /// Low speed
+    Low = 0b01,
+    /// High speed
+    High = 0b10,
+    /// Very high speed
+    VeryHigh = 0b11,
+}
+
+/// Alternate function configuration of a [Dynamic] pin
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AfConfig {
+    /// Alternate function number, 0 to 15
+    pub af: u8,
+    /// Pull resistor
+    pub pull: Pull,
+    /// Output speed
+    pub speed: Speed,
+    /// Open drain output stage instead of push-pull
+    pub open_drain: bool,
+}
+
+impl AfConfig {
+    /// Push-pull alternate function `af` without pull resistor, at very low speed
+    pub const fn new(af: u8) -> Self {
+        Self {
+            af,
+            pull: Pull::None,
+            speed: Speed::VeryLow,
+            open_drain: false,
+        }
+    }
+
+    /// Select the pull resistor
+    pub const fn pull(mut self, pull: Pull) -> Self {
+        self.pull = pull;
+        self
+    }
+
+    /// Select the output speed
+    pub const fn speed(mut self, speed: Speed) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Use an open drain output stage
+    pub const fn open_drain(mut self) -> Self {
+        self.open_drain = true;
+        self
+    }
+}
+
 /// Tracks the current pin state for dynamic pins
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Dynamic {
     /// Pin is in `Input<Floating>` state
     InputFloating,
@@ -371,6 +444,10 @@ pub enum Dynamic {
     OutputPushPull,
     /// Pin is in `Output<OpenDrain>` state
     OutputOpenDrain,
+    /// Pin is in `Analog` state
+    Analog,
+    /// Pin is in an `Alternate<AFx>` state
+    Alternate(AfConfig),
 }
 
 impl Default for Dynamic {
@@ -394,17 +471,64 @@ impl Dynamic {
         use Dynamic::*;
         match self {
             InputFloating | InputPullUp | InputPullDown | OutputOpenDrain => true,
-            OutputPushPull => false,
+            OutputPushPull | Analog | Alternate(_) => false,
         }
     }
 
     fn is_output(&self) -> bool {
         use Dynamic::*;
         match self {
-            InputFloating | InputPullUp | InputPullDown => false,
+            InputFloating | InputPullUp | InputPullDown | Analog | Alternate(_) => false,
             OutputPushPull | OutputOpenDrain => true,
         }
     }
+
+    /// Write this mode to the registers of pin `n` of port `P`
+    fn apply<const P: char>(&self, n: u8) {
+        use Dynamic::*;
+        match *self {
+            InputFloating => {
+                set_pull::<P>(n, Pull::None);
+                set_moder::<P>(n, Mode::Input);
+            }
+            InputPullUp => {
+                set_pull::<P>(n, Pull::Up);
+                set_moder::<P>(n, Mode::Input);
+            }
+            InputPullDown => {
+                set_pull::<P>(n, Pull::Down);
+                set_moder::<P>(n, Mode::Input);
+            }
+            OutputPushPull => {
+                set_pull::<P>(n, Pull::None);
+                set_otype::<P>(n, Cnf::PushPull);
+                set_moder::<P>(n, Mode::Output);
+            }
+            OutputOpenDrain => {
+                set_pull::<P>(n, Pull::None);
+                set_otype::<P>(n, Cnf::OpenDrain);
+                set_moder::<P>(n, Mode::Output);
+            }
+            Analog => {
+                set_pull::<P>(n, Pull::None);
+                set_moder::<P>(n, Mode::Analog);
+            }
+            Alternate(cfg) => {
+                set_pull::<P>(n, cfg.pull);
+                set_speed::<P>(n, cfg.speed);
+                set_otype::<P>(
+                    n,
+                    if cfg.open_drain {
+                        Cnf::OpenDrain
+                    } else {
+                        Cnf::PushPull
+                    },
+                );
+                set_af::<P>(n, cfg.af);
+                set_moder::<P>(n, Mode::Alternate);
+            }
+        }
+    }
 }
 
 macro_rules! gpio {
@@ -587,7 +711,7 @@ where
     /// Erases the pin number from the type
     #[inline]
     pub fn erase_number(self) -> PartiallyErasedPin<P, MODE> {
-        PartiallyErasedPin::new(N)
+        PartiallyErasedPin::new(N, self.mode)
     }
 }
 
@@ -670,6 +794,20 @@ impl<const P: char, const N: u8> Pin<P, N, Output<OpenDrain>> {
     }
 }
 
+impl<const P: char, const N: u8, AF> Pin<P, N, Alternate<AF>> {
+    /// Drives the pin open-drain instead of push-pull
+    #[inline]
+    pub fn set_open_drain(&mut self, open_drain: bool) {
+        set_otype::<P>(N, if open_drain { Cnf::OpenDrain } else { Cnf::PushPull });
+    }
+
+    /// Selects the internal pull resistor
+    #[inline]
+    pub fn set_internal_pull(&mut self, pull: Pull) {
+        set_pull::<P>(N, pull);
+    }
+}
+
 impl<const P: char, const N: u8, MODE> Pin<P, N, MODE>
 where
     MODE: Active,
@@ -841,8 +979,8 @@ where
     /// and output without changing the type. It starts out
     /// as a floating input
     #[inline]
-    pub fn into_dynamic(mut self) -> Pin<P, N, Dynamic> {
-        self.mode::<Input<Floating>>();
+    pub fn into_dynamic(self) -> Pin<P, N, Dynamic> {
+        Dynamic::InputFloating.apply::<P>(N);
         Pin::new()
     }
 }
@@ -923,41 +1061,66 @@ impl<const P: char, const N: u8> Pin<P, N, Dynamic> {
     /// Change pin mode to a `Input<PullUp>`
     #[inline]
     pub fn make_pull_up_input(&mut self) {
-        // NOTE(unsafe), we have a mutable reference to the current pin
-        self.mode::<Input<PullUp>>();
-        self.mode = Dynamic::InputPullUp;
+        self.make(Dynamic::InputPullUp);
     }
 
     /// Change pin mode to a `Input<PullDown>`
     #[inline]
     pub fn make_pull_down_input(&mut self) {
-        // NOTE(unsafe), we have a mutable reference to the current pin
-        self.mode::<Input<PullDown>>();
-        self.mode = Dynamic::InputPullDown;
+        self.make(Dynamic::InputPullDown);
     }
 
     /// Change pin mode to a `Input<Floating>`
     #[inline]
     pub fn make_floating_input(&mut self) {
-        // NOTE(unsafe), we have a mutable reference to the current pin
-        self.mode::<Input<Floating>>();
-        self.mode = Dynamic::InputFloating;
+        self.make(Dynamic::InputFloating);
     }
 
     /// Change pin mode to a `Output<PushPull>`
     #[inline]
     pub fn make_push_pull_output(&mut self) {
-        // NOTE(unsafe), we have a mutable reference to the current pin
-        self.mode::<Output<PushPull>>();
-        self.mode = Dynamic::OutputPushPull;
+        self.make(Dynamic::OutputPushPull);
     }
 
     /// Change mode of pin to a `Output<OpenDrain>`
     #[inline]
     pub fn make_open_drain_output(&mut self) {
+        self.make(Dynamic::OutputOpenDrain);
+    }
+
+    /// Change mode of pin to `Analog`, for example to sample it with the ADC
+    #[inline]
+    pub fn make_analog(&mut self) {
+        self.make(Dynamic::Analog);
+    }
+
+    /// Change mode of pin to the alternate function described by `config`
+    #[inline]
+    pub fn make_alternate(&mut self, config: AfConfig) {
+        self.make(Dynamic::Alternate(config));
+    }
+
+    /// Change pin mode to `mode`
+    #[inline]
+    pub fn make(&mut self, mode: Dynamic) {
         // NOTE(unsafe), we have a mutable reference to the current pin
-        self.mode::<Output<OpenDrain>>();
-        self.mode = Dynamic::OutputOpenDrain;
+        mode.apply::<P>(N);
+        self.mode = mode;
+    }
+
+    /// Returns the current mode of the pin
+    #[inline]
+    pub fn get_mode(&self) -> Dynamic {
+        self.mode
+    }
+
+    /// Set the output speed, used in output and alternate function modes
+    #[inline]
+    pub fn set_speed(&mut self, speed: Speed) {
+        set_speed::<P>(N, speed);
+        if let Dynamic::Alternate(cfg) = &mut self.mode {
+            cfg.speed = speed;
+        }
     }
 }
 
@@ -1091,57 +1254,20 @@ impl PinMode for Alternate<AF15> {
 
 impl<const P: char, const N: u8, M> Pin<P, N, M> {
     fn mode<MODE: PinMode>(&mut self) {
-        let gpio = unsafe { &(*gpiox::<P>()) };
-
-        let offset2 = 2 * N;
         // set pull up/down if necessary
         if let Some(pull) = MODE::PULL {
-            let pupdv = if pull { 0b01 } else { 0b11 };
-            unsafe {
-                gpio.pupdr
-                    .modify(|r, w| w.bits((r.bits() & !(0b11 << offset2)) | (pupdv << offset2)))
-            };
+            set_pull::<P>(N, if pull { Pull::Up } else { Pull::Down });
         }
 
         // set the mode
-        let mv: u8 = MODE::MODE.into();
-        unsafe {
-            gpio.moder
-                .modify(|r, w| w.bits((r.bits() & !(0b11 << offset2)) | ((mv as u32) << offset2)))
-        };
+        set_moder::<P>(N, MODE::MODE);
         // if an output, set output type
         if MODE::MODE == Mode::Output {
-            let otv = match MODE::CNF {
-                Cnf::OpenDrain => 0b1,
-                Cnf::PushPull => 0b0,
-            };
-            gpio.otyper
-                .modify(|r, w| unsafe { w.bits((r.bits() & !(0b1 << N)) | (otv << N)) });
+            set_otype::<P>(N, MODE::CNF);
         }
         // if an alternate function pin, set that
         if let Some(af) = MODE::AF {
-            let afv: u8 = af.into();
-            unsafe {
-                if N < 8 {
-                    let offset4 = N * 4;
-                    gpio.afrl.modify(|r, w| {
-                        w.bits((r.bits() & !(0b1111 << offset4)) | ((afv as u32) << offset4))
-                    });
-                } else {
-                    #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
-                    {
-                        let offset4 = (N - 8) * 4;
-                        gpio.afrh.modify(|r, w| {
-                            w.bits((r.bits() & !(0b1111 << offset4)) | ((afv as u32) << offset4))
-                        });
-                    }
-                    #[cfg(feature = "py32f002b")]
-                    {
-                        // py32f002b does only have a maximum of 8 pins per port so it does not have AFRH
-                        unreachable!();
-                    }
-                }
-            }
+            set_af::<P>(N, af.into());
         }
     }
 
@@ -1152,7 +1278,12 @@ impl<const P: char, const N: u8, M> Pin<P, N, M> {
     }
 }
 
-#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002a",
+    feature = "py32f072"
+))]
 gpio!(GPIOA, gpioa, PAx, 'A', [
     PA0: (pa0, 0),
     PA1: (pa1, 1),
@@ -1197,6 +1328,26 @@ gpio!(GPIOB, gpiob, PBx, 'B', [
     PB8: (pb8, 8),
 ]);
 
+#[cfg(feature = "py32f072")]
+gpio!(GPIOB, gpiob, PBx, 'B', [
+    PB0: (pb0, 0),
+    PB1: (pb1, 1),
+    PB2: (pb2, 2),
+    PB3: (pb3, 3),
+    PB4: (pb4, 4),
+    PB5: (pb5, 5),
+    PB6: (pb6, 6),
+    PB7: (pb7, 7),
+    PB8: (pb8, 8),
+    PB9: (pb9, 9),
+    PB10: (pb10, 10),
+    PB11: (pb11, 11),
+    PB12: (pb12, 12),
+    PB13: (pb13, 13),
+    PB14: (pb14, 14),
+    PB15: (pb15, 15),
+]);
+
 #[cfg(feature = "py32f002b")]
 gpio!(GPIOB, gpiob, PBx, 'B', [
     PB0: (pb0, 0),
@@ -1216,6 +1367,26 @@ gpio!(GPIOC, gpioc, PCx, 'C', [
     PC1: (pc1, 1),
 ]);
 
+#[cfg(feature = "py32f072")]
+gpio!(GPIOC, gpioc, PCx, 'C', [
+    PC0: (pc0, 0),
+    PC1: (pc1, 1),
+    PC2: (pc2, 2),
+    PC3: (pc3, 3),
+    PC4: (pc4, 4),
+    PC5: (pc5, 5),
+    PC6: (pc6, 6),
+    PC7: (pc7, 7),
+    PC8: (pc8, 8),
+    PC9: (pc9, 9),
+    PC10: (pc10, 10),
+    PC11: (pc11, 11),
+    PC12: (pc12, 12),
+    PC13: (pc13, 13),
+    PC14: (pc14, 14),
+    PC15: (pc15, 15),
+]);
+
 #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
 gpio!(GPIOF, gpiof, PFx, 'F', [
     PF0: (pf0, 0),
@@ -1225,13 +1396,108 @@ gpio!(GPIOF, gpiof, PFx, 'F', [
     PF4: (pf4, 4),
 ]);
 
+#[cfg(feature = "py32f072")]
+gpio!(GPIOF, gpiof, PFx, 'F', [
+    PF0: (pf0, 0),
+    PF1: (pf1, 1),
+    PF2: (pf2, 2),
+    PF3: (pf3, 3),
+    PF4: (pf4, 4),
+    PF5: (pf5, 5),
+    PF6: (pf6, 6),
+    PF7: (pf7, 7),
+]);
+
+#[inline(always)]
+fn set_pull<const P: char>(n: u8, pull: Pull) {
+    let gpio = unsafe { &(*gpiox::<P>()) };
+    let offset2 = 2 * n;
+    let pupdv: u32 = match pull {
+        Pull::None => 0b00,
+        Pull::Up => 0b01,
+        Pull::Down => 0b10,
+    };
+    unsafe {
+        gpio.pupdr
+            .modify(|r, w| w.bits((r.bits() & !(0b11 << offset2)) | (pupdv << offset2)))
+    };
+}
+
+#[inline(always)]
+fn set_speed<const P: char>(n: u8, speed: Speed) {
+    let gpio = unsafe { &(*gpiox::<P>()) };
+    let offset2 = 2 * n;
+    unsafe {
+        gpio.ospeedr.modify(|r, w| {
+            w.bits((r.bits() & !(0b11 << offset2)) | ((speed as u32) << offset2))
+        })
+    };
+}
+
+#[inline(always)]
+fn set_moder<const P: char>(n: u8, mode: Mode) {
+    let gpio = unsafe { &(*gpiox::<P>()) };
+    let offset2 = 2 * n;
+    let mv: u8 = mode.into();
+    unsafe {
+        gpio.moder
+            .modify(|r, w| w.bits((r.bits() & !(0b11 << offset2)) | ((mv as u32) << offset2)))
+    };
+}
+
+#[inline(always)]
+fn set_otype<const P: char>(n: u8, cnf: Cnf) {
+    let gpio = unsafe { &(*gpiox::<P>()) };
+    let otv = match cnf {
+        Cnf::OpenDrain => 0b1,
+        Cnf::PushPull => 0b0,
+    };
+    gpio.otyper
+        .modify(|r, w| unsafe { w.bits((r.bits() & !(0b1 << n)) | (otv << n)) });
+}
+
+#[inline(always)]
+fn set_af<const P: char>(n: u8, af: u8) {
+    let gpio = unsafe { &(*gpiox::<P>()) };
+    let afv = (af & 0xf) as u32;
+    unsafe {
+        if n < 8 {
+            let offset4 = n * 4;
+            gpio.afrl
+                .modify(|r, w| w.bits((r.bits() & !(0b1111 << offset4)) | (afv << offset4)));
+        } else {
+            #[cfg(any(
+                feature = "py32f030",
+                feature = "py32f003",
+                feature = "py32f002a",
+                feature = "py32f072"
+            ))]
+            {
+                let offset4 = (n - 8) * 4;
+                gpio.afrh
+                    .modify(|r, w| w.bits((r.bits() & !(0b1111 << offset4)) | (afv << offset4)));
+            }
+            #[cfg(feature = "py32f002b")]
+            {
+                // py32f002b does only have a maximum of 8 pins per port so it does not have AFRH
+                unreachable!();
+            }
+        }
+    }
+}
+
 const fn gpiox<const P: char>() -> *const crate::pac::gpioa::RegisterBlock {
     match P {
         'A' => crate::pac::GPIOA::ptr(),
         'B' => crate::pac::GPIOB::ptr() as _,
-        #[cfg(feature = "py32f002b")]
+        #[cfg(any(feature = "py32f002b", feature = "py32f072"))]
         'C' => crate::pac::GPIOC::ptr() as _,
-        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+        #[cfg(any(
+            feature = "py32f030",
+            feature = "py32f003",
+            feature = "py32f002a",
+            feature = "py32f072"
+        ))]
         'F' => crate::pac::GPIOF::ptr() as _,
         _ => unreachable!(),
     }
@@ -75,6 +75,39 @@ macro_rules! impl_pxx {
             }
         }
 
+        impl ErasedPin<Dynamic> {
+            /// Change pin mode to `mode`
+            pub fn make(&mut self, mode: Dynamic) {
+                match self {
+                    $(Self::$pin(pin) => pin.make(mode)),*
+                }
+            }
+            /// Returns the current mode of the pin
+            pub fn get_mode(&self) -> Dynamic {
+                match self {
+                    $(Self::$pin(pin) => pin.get_mode()),*
+                }
+            }
+            /// Set the output speed, used in output and alternate function modes
+            pub fn set_speed(&mut self, speed: Speed) {
+                match self {
+                    $(Self::$pin(pin) => pin.set_speed(speed)),*
+                }
+            }
+            /// Set the [PinState] of a pin, if it is in an output mode
+            pub fn set_state(&mut self, state: PinState) -> Result<(), PinModeError> {
+                match self {
+                    $(Self::$pin(pin) => pin.set_state(state)),*
+                }
+            }
+            /// returns true if pin is at low level, if it is in an input mode
+            pub fn is_low(&self) -> Result<bool, PinModeError> {
+                match self {
+                    $(Self::$pin(pin) => pin.is_low()),*
+                }
+            }
+        }
+
         impl ErasedPin<Output<OpenDrain>> {
             /// returns true if pin is at high level
             pub fn is_high(&self) -> bool {
@@ -124,6 +157,69 @@ impl<MODE> ErasedPin<Output<MODE>> {
     }
 }
 
+/// [Dynamic] pin functions
+impl ErasedPin<Dynamic> {
+    /// Change pin mode to a `Input<PullUp>`
+    #[inline]
+    pub fn make_pull_up_input(&mut self) {
+        self.make(Dynamic::InputPullUp);
+    }
+
+    /// Change pin mode to a `Input<PullDown>`
+    #[inline]
+    pub fn make_pull_down_input(&mut self) {
+        self.make(Dynamic::InputPullDown);
+    }
+
+    /// Change pin mode to a `Input<Floating>`
+    #[inline]
+    pub fn make_floating_input(&mut self) {
+        self.make(Dynamic::InputFloating);
+    }
+
+    /// Change pin mode to a `Output<PushPull>`
+    #[inline]
+    pub fn make_push_pull_output(&mut self) {
+        self.make(Dynamic::OutputPushPull);
+    }
+
+    /// Change mode of pin to a `Output<OpenDrain>`
+    #[inline]
+    pub fn make_open_drain_output(&mut self) {
+        self.make(Dynamic::OutputOpenDrain);
+    }
+
+    /// Change mode of pin to `Analog`
+    #[inline]
+    pub fn make_analog(&mut self) {
+        self.make(Dynamic::Analog);
+    }
+
+    /// Change mode of pin to the alternate function described by `config`
+    #[inline]
+    pub fn make_alternate(&mut self, config: AfConfig) {
+        self.make(Dynamic::Alternate(config));
+    }
+
+    /// Set a pin to high level, if it is in an output mode
+    #[inline]
+    pub fn set_high(&mut self) -> Result<(), PinModeError> {
+        self.set_state(PinState::High)
+    }
+
+    /// Set a pin to low level, if it is in an output mode
+    #[inline]
+    pub fn set_low(&mut self) -> Result<(), PinModeError> {
+        self.set_state(PinState::Low)
+    }
+
+    /// returns true if pin is at high level, if it is in an input mode
+    #[inline]
+    pub fn is_high(&self) -> Result<bool, PinModeError> {
+        self.is_low().map(|b| !b)
+    }
+}
+
 #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
 impl_pxx! {
     ('A'::PAx),
@@ -131,6 +227,14 @@ impl_pxx! {
     ('F'::PFx)
 }
 
+#[cfg(feature = "py32f072")]
+impl_pxx! {
+    ('A'::PAx),
+    ('B'::PBx),
+    ('C'::PCx),
+    ('F'::PFx)
+}
+
 #[cfg(feature = "py32f002b")]
 impl_pxx! {
     ('A'::PAx),
@@ -9,15 +9,12 @@ pub type PEPin<const P: char, MODE> = PartiallyErasedPin<P, MODE>;
 /// - `P` is port name: `A` for GPIOA, `B` for GPIOB, etc.
 pub struct PartiallyErasedPin<const P: char, MODE> {
     pin_number: u8,
-    _mode: PhantomData<MODE>,
+    mode: MODE,
 }
 
 impl<const P: char, MODE> PartiallyErasedPin<P, MODE> {
-    pub(crate) fn new(pin_number: u8) -> Self {
-        Self {
-            pin_number,
-            _mode: PhantomData,
-        }
+    pub(crate) fn new(pin_number: u8, mode: MODE) -> Self {
+        Self { pin_number, mode }
     }
 }
 
@@ -119,3 +116,115 @@ impl<const P: char, MODE> PartiallyErasedPin<P, Input<MODE>> {
         gpio.idr.read().bits() & (1 << self.pin_number) != 0
     }
 }
+
+/// [Dynamic] pin functions
+impl<const P: char> PartiallyErasedPin<P, Dynamic> {
+    /// Change pin mode to a `Input<PullUp>`
+    #[inline]
+    pub fn make_pull_up_input(&mut self) {
+        self.make(Dynamic::InputPullUp);
+    }
+
+    /// Change pin mode to a `Input<PullDown>`
+    #[inline]
+    pub fn make_pull_down_input(&mut self) {
+        self.make(Dynamic::InputPullDown);
+    }
+
+    /// Change pin mode to a `Input<Floating>`
+    #[inline]
+    pub fn make_floating_input(&mut self) {
+        self.make(Dynamic::InputFloating);
+    }
+
+    /// Change pin mode to a `Output<PushPull>`
+    #[inline]
+    pub fn make_push_pull_output(&mut self) {
+        self.make(Dynamic::OutputPushPull);
+    }
+
+    /// Change mode of pin to a `Output<OpenDrain>`
+    #[inline]
+    pub fn make_open_drain_output(&mut self) {
+        self.make(Dynamic::OutputOpenDrain);
+    }
+
+    /// Change mode of pin to `Analog`
+    #[inline]
+    pub fn make_analog(&mut self) {
+        self.make(Dynamic::Analog);
+    }
+
+    /// Change mode of pin to the alternate function described by `config`
+    #[inline]
+    pub fn make_alternate(&mut self, config: AfConfig) {
+        self.make(Dynamic::Alternate(config));
+    }
+
+    /// Change pin mode to `mode`
+    #[inline]
+    pub fn make(&mut self, mode: Dynamic) {
+        mode.apply::<P>(self.pin_number);
+        self.mode = mode;
+    }
+
+    /// Returns the current mode of the pin
+    #[inline]
+    pub fn get_mode(&self) -> Dynamic {
+        self.mode
+    }
+
+    /// Set the output speed, used in output and alternate function modes
+    #[inline]
+    pub fn set_speed(&mut self, speed: Speed) {
+        set_speed::<P>(self.pin_number, speed);
+        if let Dynamic::Alternate(cfg) = &mut self.mode {
+            cfg.speed = speed;
+        }
+    }
+
+    /// Set a pin to high level, if it is in an output mode
+    #[inline]
+    pub fn set_high(&mut self) -> Result<(), PinModeError> {
+        self.set_state(PinState::High)
+    }
+
+    /// Set a pin to low level, if it is in an output mode
+    #[inline]
+    pub fn set_low(&mut self) -> Result<(), PinModeError> {
+        self.set_state(PinState::Low)
+    }
+
+    /// Set the [PinState] of a pin, if it is in an output mode
+    #[inline]
+    pub fn set_state(&mut self, state: PinState) -> Result<(), PinModeError> {
+        if !self.mode.is_output() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic write to a stateless register
+        let gpio = unsafe { &(*gpiox::<P>()) };
+        let bit = match state {
+            PinState::High => self.pin_number,
+            PinState::Low => self.pin_number + 16,
+        };
+        unsafe { gpio.bsrr.write(|w| w.bits(1 << bit)) };
+        Ok(())
+    }
+
+    /// returns true if pin is at high level, if it is in an input mode
+    #[inline]
+    pub fn is_high(&self) -> Result<bool, PinModeError> {
+        self.is_low().map(|b| !b)
+    }
+
+    /// returns true if pin is at low level, if it is in an input mode
+    #[inline]
+    pub fn is_low(&self) -> Result<bool, PinModeError> {
+        if !self.mode.is_input() {
+            return Err(PinModeError::IncorrectMode);
+        }
+        // NOTE(unsafe) atomic read with no side effects
+        let gpio = unsafe { &(*gpiox::<P>()) };
+        Ok(gpio.idr.read().bits() & (1 << self.pin_number) == 0)
+    }
+}
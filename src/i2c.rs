@@ -21,104 +21,40 @@ pub trait SclPin<I2C> {}
 /// Trait for identifying SDA pins
 pub trait SdaPin<I2C> {}
 
-macro_rules! i2c_pins {
-    ($($I2C:ident => {
-        scl => [$($scl:ty),+ $(,)*],
-        sda => [$($sda:ty),+ $(,)*],
-    })+) => {
-        $(
-            $(
-                impl SclPin<crate::pac::$I2C> for $scl {}
-            )+
-            $(
-                impl SdaPin<crate::pac::$I2C> for $sda {}
-            )+
-        )+
-    }
-}
+// afgen:begin i2c
+// Generated by tools/afgen.py from tools/af/*.csv, do not edit by hand
 
-#[cfg(feature = "py32f030")]
-i2c_pins! {
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+af_pins! {
     I2C => {
-        scl => [
-            gpioa::PA3<Alternate<AF12>>,
-            gpioa::PA8<Alternate<AF12>>,
-            gpioa::PA9<Alternate<AF6>>,
-            gpioa::PA10<Alternate<AF12>>,
-            gpioa::PA11<Alternate<AF6>>,
-            gpiob::PB6<Alternate<AF6>>,
-            gpiob::PB8<Alternate<AF6>>,
-            gpiof::PF1<Alternate<AF12>>
-        ],
-        sda => [
-            gpioa::PA2<Alternate<AF12>>,
-            gpioa::PA7<Alternate<AF12>>,
-            gpioa::PA9<Alternate<AF12>>,
-            gpioa::PA10<Alternate<AF6>>,
-            gpioa::PA12<Alternate<AF6>>,
-            gpiob::PB7<Alternate<AF6>>,
-            gpiof::PF0<Alternate<AF12>>
-        ],
+        SclPin => [PA3<AF12>, PA10<AF12>, PB6<AF6>, PF1<AF12>],
+        SdaPin => [PA2<AF12>, PA7<AF12>, PA10<AF6>, PA12<AF6>, PB7<AF6>, PF0<AF12>],
     }
 }
 
-#[cfg(feature = "py32f002a")]
-i2c_pins! {
+#[cfg(any(feature = "py32f030", feature = "py32f002a"))]
+af_pins! {
     I2C => {
-        scl => [
-            gpioa::PA3<Alternate<AF12>>,
-            gpioa::PA8<Alternate<AF12>>,
-            gpioa::PA9<Alternate<AF6>>,
-            gpioa::PA10<Alternate<AF12>>,
-            gpiob::PB6<Alternate<AF6>>,
-            gpiob::PB8<Alternate<AF6>>,
-            gpiof::PF1<Alternate<AF12>>
-        ],
-        sda => [
-            gpioa::PA2<Alternate<AF12>>,
-            gpioa::PA7<Alternate<AF12>>,
-            gpioa::PA9<Alternate<AF12>>,
-            gpioa::PA10<Alternate<AF6>>,
-            gpioa::PA12<Alternate<AF6>>,
-            gpiob::PB7<Alternate<AF6>>,
-            gpiof::PF0<Alternate<AF12>>
-        ],
+        SclPin => [PA8<AF12>, PA9<AF6>, PB8<AF6>],
+        SdaPin => [PA9<AF12>],
     }
 }
 
-#[cfg(feature = "py32f003")]
-i2c_pins! {
+#[cfg(feature = "py32f030")]
+af_pins! {
     I2C => {
-        scl => [
-            gpioa::PA3<Alternate<AF12>>,
-            gpioa::PA10<Alternate<AF12>>,  // PA10 with AF12 for SCL (from pinout table)
-            gpiob::PB6<Alternate<AF6>>,
-            gpiof::PF1<Alternate<AF12>>
-        ],
-        sda => [
-            gpioa::PA2<Alternate<AF12>>,
-            gpioa::PA7<Alternate<AF12>>,
-            gpioa::PA10<Alternate<AF6>>,   // PA10 with AF6 for SDA (from pinout table)
-            gpioa::PA12<Alternate<AF6>>,
-            gpiob::PB7<Alternate<AF6>>,
-            gpiof::PF0<Alternate<AF12>>
-        ],
+        SclPin => [PA11<AF6>],
     }
 }
 
 #[cfg(feature = "py32f002b")]
-i2c_pins! {
+af_pins! {
     I2C => {
-        scl => [
-            gpioa::PA2<Alternate<AF6>>,
-            gpiob::PB3<Alternate<AF6>>,
-        ],
-        sda => [
-            gpiob::PB4<Alternate<AF6>>,
-            gpiob::PB6<Alternate<AF6>>,
-        ],
+        SclPin => [PA2<AF6>, PB3<AF6>],
+        SdaPin => [PB4<AF6>, PB6<AF6>],
     }
 }
+// afgen:end i2c
 
 /// Error enum for I2C peripheral
 #[derive(Debug)]
@@ -138,7 +74,11 @@ pub enum Error {
 
 // It's s needed for the impls, but rustc doesn't recognize that
 #[allow(dead_code)]
+#[cfg(not(feature = "py32f072"))]
 type I2cRegisterBlock = crate::pac::i2c::RegisterBlock;
+#[allow(dead_code)]
+#[cfg(feature = "py32f072")]
+type I2cRegisterBlock = crate::pac::i2c1::RegisterBlock;
 
 /// trait for I2C peripheral instance
 pub trait Instance: Deref<Target = I2cRegisterBlock> + crate::Sealed + Enable + Reset {
@@ -176,10 +116,17 @@ macro_rules! i2c {
     }
 }
 
+#[cfg(not(feature = "py32f072"))]
 i2c! {
     I2C: i2c,
 }
 
+#[cfg(feature = "py32f072")]
+i2c! {
+    I2C1: i2c1,
+    I2C2: i2c2,
+}
+
 impl<I2C, SCLPIN, SDAPIN> I2c<I2C, SCLPIN, SDAPIN>
 where
     I2C: Instance,
@@ -209,9 +156,14 @@ where
                 },
             )
         };
+        #[cfg(not(feature = "py32f072"))]
         self.i2c
             .ccr
             .modify(|_, w| unsafe { w.f_s().bit(f_s).ccr().bits(ccr.clamp(4, 4095) as u16) });
+        #[cfg(feature = "py32f072")]
+        self.i2c
+            .ccr
+            .modify(|_, w| unsafe { w.fs().bit(f_s).ccr().bits(ccr.clamp(4, 4095) as u16) });
 
         // Enable the I2C processing
         self.i2c.cr1.modify(|_, w| w.pe().set_bit());
@@ -219,6 +171,13 @@ where
         self
     }
 
+    fn set_address(&mut self, addr: u8) {
+        #[cfg(not(feature = "py32f072"))]
+        self.i2c.oar1.modify(|_, w| w.add().bits(addr));
+        #[cfg(feature = "py32f072")]
+        self.i2c.oar1.modify(|_, w| w.add1_7().bits(addr));
+    }
+
     /// Release the I2C instance
     pub fn release(self) -> (I2C, (SCLPIN, SDAPIN)) {
         (self.i2c, self.pins)
@@ -293,7 +252,7 @@ where
 
     fn write_read(&mut self, addr: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Error> {
         // Set up current slave address for writing and disable autoending
-        self.i2c.oar1.modify(|_, w| w.add().bits(addr));
+        self.set_address(addr);
 
         // Send a START condition
         self.i2c.cr1.modify(|_, w| w.start().set_bit());
@@ -322,7 +281,7 @@ where
         }
 
         // Set up current address for reading
-        self.i2c.oar1.modify(|_, w| w.add().bits(addr));
+        self.set_address(addr);
 
         // Send another START condition
         self.i2c.cr1.modify(|_, w| w.start().set_bit());
@@ -350,7 +309,7 @@ where
 
     fn read(&mut self, addr: u8, buffer: &mut [u8]) -> Result<(), Error> {
         // Set up current address for reading
-        self.i2c.oar1.modify(|_, w| w.add().bits(addr));
+        self.set_address(addr);
 
         // Send a START condition
         self.i2c.cr1.modify(|_, w| w.start().set_bit());
@@ -378,7 +337,7 @@ where
 
     fn write(&mut self, addr: u8, bytes: &[u8]) -> Result<(), Error> {
         // Set up current slave address for writing and enable autoending
-        self.i2c.oar1.modify(|_, w| w.add().bits(addr));
+        self.set_address(addr);
 
         // Send a START condition
         self.i2c.cr1.modify(|_, w| w.start().set_bit());
@@ -19,6 +19,9 @@
 //! - `py32f002b`
 //! - `py32f003`
 //! - `py32f030`
+//! - `py32f072`, partially: the `adc` and `dma` modules are not built for it, and its pins have
+//!   no alternate function table, so the `spi` and `i2c` modules, the timer PWM and the
+//!   synchronous serial mode are left out as well
 //!
 //! ## Commonly used setup
 //! Almost all peripherals require references to some registers in `RCC`. The following
@@ -61,25 +64,34 @@ pub use py32f0::py32f002b as pac;
 pub use py32f0::py32f003 as pac;
 #[cfg(all(feature = "py32f030", not(any(feature = "py32f002a", feature = "py32f002b", feature = "py32f003"))))]
 pub use py32f0::py32f030 as pac;
+#[cfg(all(feature = "py32f072", not(any(feature = "py32f002a", feature = "py32f002b", feature = "py32f003", feature = "py32f030"))))]
+pub use py32f0::py32f072 as pac;
 
-#[cfg(feature = "device-selected")]
+#[cfg(all(feature = "device-selected", not(feature = "py32f072")))]
 pub mod adc;
+pub mod baud;
 #[cfg(all(feature = "device-selected", feature = "with-dma"))]
 pub mod dma;
 #[cfg(feature = "device-selected")]
-pub mod gpio;
+pub mod exti;
 #[cfg(feature = "device-selected")]
+pub mod gpio;
+#[cfg(all(feature = "device-selected", not(feature = "py32f072")))]
 pub mod i2c;
+pub mod lin;
+#[cfg(feature = "modbus")]
+pub mod modbus;
 #[cfg(feature = "device-selected")]
 pub mod prelude;
 #[cfg(feature = "device-selected")]
 pub mod rcc;
-#[cfg(any(feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 pub mod rtc;
 #[cfg(feature = "device-selected")]
 pub mod serial;
-#[cfg(feature = "device-selected")]
+#[cfg(all(feature = "device-selected", not(feature = "py32f072")))]
 pub mod spi;
+pub mod spi_prescaler;
 #[cfg(feature = "device-selected")]
 pub mod system_init;
 #[cfg(feature = "device-selected")]
@@ -88,8 +100,13 @@ pub mod time;
 pub mod timer;
 #[cfg(feature = "device-selected")]
 pub mod watchdog;
+#[cfg(feature = "device-selected")]
+mod waker;
 
 mod sealed {
     pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u16 {}
 }
 use sealed::Sealed;
@@ -0,0 +1,151 @@
+//! LIN frame encoding
+//!
+//! The parts of the LIN protocol that do not depend on the USART: the sync byte, the protected
+//! identifier with its two parity bits and the classic and enhanced checksums. It has no
+//! hardware dependencies, the USART driver is [`serial::Lin`](crate::serial::Lin) on the
+//! py32f072.
+//!
+//! ```rust
+//! let pid = lin::protected_id(0x10).unwrap();
+//! let checksum = lin::checksum(lin::ChecksumKind::Enhanced, pid, &[0x01, 0x02]);
+//! ```
+
+/// Byte following the break field of every header
+pub const SYNC: u8 = 0x55;
+
+/// Largest frame identifier
+pub const MAX_ID: u8 = 0x3f;
+
+/// Largest number of data bytes in a frame
+pub const MAX_DATA_LEN: usize = 8;
+
+/// Identifier of the master request diagnostic frame
+pub const MASTER_REQUEST_ID: u8 = 0x3c;
+
+/// Identifier of the slave response diagnostic frame
+pub const SLAVE_RESPONSE_ID: u8 = 0x3d;
+
+/// Checksum model of a frame
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChecksumKind {
+    /// LIN 1.x checksum over the data bytes
+    Classic,
+    /// LIN 2.x checksum over the protected identifier and the data bytes
+    ///
+    /// The diagnostic frames `0x3c` and `0x3d` always use the classic checksum.
+    Enhanced,
+}
+
+/// Returns the protected identifier of the frame identifier `id`, or `None` if `id` is larger
+/// than [MAX_ID]
+pub const fn protected_id(id: u8) -> Option<u8> {
+    if id > MAX_ID {
+        return None;
+    }
+    let p0 = (id ^ (id >> 1) ^ (id >> 2) ^ (id >> 4)) & 1;
+    let p1 = !((id >> 1) ^ (id >> 3) ^ (id >> 4) ^ (id >> 5)) & 1;
+    Some(id | (p0 << 6) | (p1 << 7))
+}
+
+/// Returns the frame identifier of the protected identifier `pid`, or `None` if its parity bits
+/// are wrong
+pub const fn frame_id(pid: u8) -> Option<u8> {
+    let id = pid & MAX_ID;
+    match protected_id(id) {
+        Some(expected) if expected == pid => Some(id),
+        _ => None,
+    }
+}
+
+/// Computes the checksum of a frame with protected identifier `pid`
+pub fn checksum(kind: ChecksumKind, pid: u8, data: &[u8]) -> u8 {
+    let id = pid & MAX_ID;
+    let initial = match kind {
+        ChecksumKind::Enhanced if id != MASTER_REQUEST_ID && id != SLAVE_RESPONSE_ID => pid as u16,
+        _ => 0,
+    };
+    let sum = data.iter().fold(initial, |sum, &byte| {
+        // add with carry, the carry is folded back into the low byte
+        let sum = sum + byte as u16;
+        if sum > 0xff {
+            sum - 0xff
+        } else {
+            sum
+        }
+    });
+    !(sum as u8)
+}
+
+/// Returns true if `checksum` is the checksum of the frame
+pub fn verify_checksum(kind: ChecksumKind, pid: u8, data: &[u8], checksum: u8) -> bool {
+    self::checksum(kind, pid, data) == checksum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protected_ids() {
+        let vectors = [
+            (0x00, 0x80),
+            (0x01, 0xc1),
+            (0x02, 0x42),
+            (0x03, 0x03),
+            (0x10, 0x50),
+            (0x20, 0x20),
+            (0x3c, 0x3c),
+            (0x3d, 0x7d),
+            (0x3e, 0xfe),
+            (0x3f, 0xbf),
+        ];
+        for (id, pid) in vectors {
+            assert_eq!(protected_id(id), Some(pid), "id {id:#04x}");
+            assert_eq!(frame_id(pid), Some(id), "pid {pid:#04x}");
+        }
+        assert_eq!(protected_id(0x40), None);
+        for id in 0..=MAX_ID {
+            assert_eq!(frame_id(protected_id(id).unwrap()), Some(id));
+        }
+    }
+
+    #[test]
+    fn wrong_parity() {
+        assert_eq!(frame_id(0x3d), None);
+        assert_eq!(frame_id(0x00), None);
+        assert_eq!(frame_id(0xff), None);
+    }
+
+    #[test]
+    fn classic_checksum() {
+        // example of the LIN specification
+        let data = [0x4a, 0x55, 0x93, 0xe5];
+        assert_eq!(checksum(ChecksumKind::Classic, 0x50, &data), 0xe6);
+        assert!(verify_checksum(ChecksumKind::Classic, 0x50, &data, 0xe6));
+        assert_eq!(checksum(ChecksumKind::Classic, 0x50, &[]), 0xff);
+    }
+
+    #[test]
+    fn enhanced_checksum() {
+        // 0x50 + 0x01 + 0x02 = 0x53
+        assert_eq!(checksum(ChecksumKind::Enhanced, 0x50, &[0x01, 0x02]), 0xac);
+        // 0xc1 + 0xff = 0x1c0, carried to 0xc1, twice
+        assert_eq!(checksum(ChecksumKind::Enhanced, 0xc1, &[0xff, 0xff]), 0x3e);
+        // 0x80 + 0x4a + 0x55 + 0x93 + 0xe5, carried to 0x99
+        let data = [0x4a, 0x55, 0x93, 0xe5];
+        assert_eq!(checksum(ChecksumKind::Enhanced, 0x80, &data), 0x66);
+        assert!(!verify_checksum(ChecksumKind::Enhanced, 0x80, &data, 0xe6));
+    }
+
+    #[test]
+    fn diagnostic_frames_use_classic_checksum() {
+        let data = [0x7f, 0x06, 0xb2, 0x00, 0xff, 0x7f, 0xff, 0xff];
+        for pid in [0x3c, 0x7d] {
+            assert_eq!(
+                checksum(ChecksumKind::Enhanced, pid, &data),
+                checksum(ChecksumKind::Classic, pid, &data)
+            );
+        }
+    }
+}
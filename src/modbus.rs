@@ -0,0 +1,911 @@
+//! Modbus RTU
+//!
+//! The framing, CRC and protocol data units of Modbus RTU, without hardware dependencies:
+//!
+//! - [crc16], [append_crc] and [decode_frame] handle the address and CRC around a PDU
+//! - a master encodes a [Request] and decodes the [Response] with [Request::decode_response]
+//! - a slave answers a request with [process_request], reading and writing coils and registers
+//!   through a [Handler]
+//!
+//! [Master] and [Slave] run them over a serial port, with the 3.5 character frame timeout taken
+//! from a timer. The port can be a [`Serial`](crate::serial::Serial) or an
+//! [`Rs485`](crate::serial::Rs485) driving the transceiver enable.
+//!
+//! ```rust
+//! let port = p.USART1.serial((tx, rx), 19_200.bps(), &clocks)?;
+//! let timer = p.TIM14.counter_us(&clocks);
+//! let mut master = Master::new(port, timer, 19_200.bps(), MicroSeconds::millis(100));
+//! let request = Request::ReadHoldingRegisters { start: 0, count: 2 };
+//! if let Response::Registers(registers) = master.request(0x11, &request)? {
+//!     let value = registers.get(0);
+//! }
+//! ```
+
+#[cfg(feature = "device-selected")]
+mod rtu;
+#[cfg(feature = "device-selected")]
+pub use rtu::{Master, RtuError, Slave};
+
+/// Largest RTU frame: address, PDU and CRC
+pub const MAX_ADU_LEN: usize = 256;
+
+/// Largest protocol data unit
+pub const MAX_PDU_LEN: usize = 253;
+
+/// Address of broadcast requests, which are not answered
+pub const BROADCAST: u8 = 0;
+
+const READ_COILS: u8 = 0x01;
+const READ_DISCRETE_INPUTS: u8 = 0x02;
+const READ_HOLDING_REGISTERS: u8 = 0x03;
+const READ_INPUT_REGISTERS: u8 = 0x04;
+const WRITE_SINGLE_COIL: u8 = 0x05;
+const WRITE_SINGLE_REGISTER: u8 = 0x06;
+const WRITE_MULTIPLE_COILS: u8 = 0x0f;
+const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+const MAX_READ_BITS: u16 = 2000;
+const MAX_READ_REGISTERS: u16 = 125;
+const MAX_WRITE_BITS: u16 = 1968;
+const MAX_WRITE_REGISTERS: u16 = 123;
+
+/// Modbus error
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Error {
+    /// The CRC of a frame is wrong
+    Crc,
+    /// A frame is shorter than an address, a function code and the CRC
+    InvalidFrame,
+    /// The request is out of the limits of the protocol or does not fit the buffer
+    InvalidRequest,
+    /// The response does not match the request
+    InvalidResponse,
+    /// The slave answered with an exception
+    Exception(Exception),
+}
+
+/// Exception code of a slave
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Exception {
+    /// The function is not supported
+    IllegalFunction,
+    /// The address is not available
+    IllegalDataAddress,
+    /// A value in the request is not allowed
+    IllegalDataValue,
+    /// The slave failed to perform the action
+    ServerDeviceFailure,
+    /// Another exception code
+    Other(u8),
+}
+
+impl Exception {
+    /// The exception code
+    pub const fn code(self) -> u8 {
+        match self {
+            Exception::IllegalFunction => 0x01,
+            Exception::IllegalDataAddress => 0x02,
+            Exception::IllegalDataValue => 0x03,
+            Exception::ServerDeviceFailure => 0x04,
+            Exception::Other(code) => code,
+        }
+    }
+
+    /// The exception of a code
+    pub const fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => Exception::IllegalFunction,
+            0x02 => Exception::IllegalDataAddress,
+            0x03 => Exception::IllegalDataValue,
+            0x04 => Exception::ServerDeviceFailure,
+            code => Exception::Other(code),
+        }
+    }
+}
+
+/// Silence in microseconds that ends a frame at `baud` bits per second
+///
+/// This is 3.5 characters of 11 bits, or 1750 µs above 19200 baud as the specification
+/// recommends.
+///
+/// # Panics
+///
+/// Panics if `baud` is zero.
+pub const fn frame_timeout_us(baud: u32) -> u32 {
+    if baud > 19_200 {
+        1750
+    } else {
+        38_500_000_u32.div_ceil(baud)
+    }
+}
+
+/// Computes the Modbus CRC-16 of `data`
+pub fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0xffff_u16;
+    for &byte in data {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xa001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Appends the CRC of `frame[..len]`, low byte first, and returns the frame length
+///
+/// # Panics
+///
+/// Panics if `frame` is shorter than `len + 2`.
+pub fn append_crc(frame: &mut [u8], len: usize) -> usize {
+    let crc = crc16(&frame[..len]);
+    frame[len..len + 2].copy_from_slice(&crc.to_le_bytes());
+    len + 2
+}
+
+/// Checks the CRC of a received frame and returns its address and PDU
+pub fn decode_frame(frame: &[u8]) -> Result<(u8, &[u8]), Error> {
+    if frame.len() < 4 {
+        return Err(Error::InvalidFrame);
+    }
+    let (data, crc) = frame.split_at(frame.len() - 2);
+    if crc16(data).to_le_bytes() != crc {
+        return Err(Error::Crc);
+    }
+    Ok((data[0], &data[1..]))
+}
+
+/// Request of a master
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Request<'a> {
+    /// Read `count` coils from `start`
+    ReadCoils {
+        /// first coil
+        start: u16,
+        /// number of coils, up to 2000
+        count: u16,
+    },
+    /// Read `count` discrete inputs from `start`
+    ReadDiscreteInputs {
+        /// first input
+        start: u16,
+        /// number of inputs, up to 2000
+        count: u16,
+    },
+    /// Read `count` holding registers from `start`
+    ReadHoldingRegisters {
+        /// first register
+        start: u16,
+        /// number of registers, up to 125
+        count: u16,
+    },
+    /// Read `count` input registers from `start`
+    ReadInputRegisters {
+        /// first register
+        start: u16,
+        /// number of registers, up to 125
+        count: u16,
+    },
+    /// Write one coil
+    WriteSingleCoil {
+        /// coil
+        address: u16,
+        /// value
+        value: bool,
+    },
+    /// Write one holding register
+    WriteSingleRegister {
+        /// register
+        address: u16,
+        /// value
+        value: u16,
+    },
+    /// Write coils from `start`
+    WriteMultipleCoils {
+        /// first coil
+        start: u16,
+        /// values, up to 1968
+        values: &'a [bool],
+    },
+    /// Write holding registers from `start`
+    WriteMultipleRegisters {
+        /// first register
+        start: u16,
+        /// values, up to 123
+        values: &'a [u16],
+    },
+}
+
+impl Request<'_> {
+    /// The function code
+    pub const fn function(&self) -> u8 {
+        match self {
+            Request::ReadCoils { .. } => READ_COILS,
+            Request::ReadDiscreteInputs { .. } => READ_DISCRETE_INPUTS,
+            Request::ReadHoldingRegisters { .. } => READ_HOLDING_REGISTERS,
+            Request::ReadInputRegisters { .. } => READ_INPUT_REGISTERS,
+            Request::WriteSingleCoil { .. } => WRITE_SINGLE_COIL,
+            Request::WriteSingleRegister { .. } => WRITE_SINGLE_REGISTER,
+            Request::WriteMultipleCoils { .. } => WRITE_MULTIPLE_COILS,
+            Request::WriteMultipleRegisters { .. } => WRITE_MULTIPLE_REGISTERS,
+        }
+    }
+
+    /// Returns true if the request writes, only those can be broadcast
+    pub const fn is_write(&self) -> bool {
+        matches!(
+            self,
+            Request::WriteSingleCoil { .. }
+                | Request::WriteSingleRegister { .. }
+                | Request::WriteMultipleCoils { .. }
+                | Request::WriteMultipleRegisters { .. }
+        )
+    }
+
+    /// Encodes the PDU into `pdu` and returns its length
+    pub fn encode(&self, pdu: &mut [u8]) -> Result<usize, Error> {
+        let (first, second, len) = match *self {
+            Request::ReadCoils { start, count } | Request::ReadDiscreteInputs { start, count } => {
+                check_range(start, count, MAX_READ_BITS)?;
+                (start, count, 5)
+            }
+            Request::ReadHoldingRegisters { start, count }
+            | Request::ReadInputRegisters { start, count } => {
+                check_range(start, count, MAX_READ_REGISTERS)?;
+                (start, count, 5)
+            }
+            Request::WriteSingleCoil { address, value } => {
+                (address, if value { 0xff00 } else { 0x0000 }, 5)
+            }
+            Request::WriteSingleRegister { address, value } => (address, value, 5),
+            Request::WriteMultipleCoils { start, values } => {
+                let count = values.len() as u16;
+                check_range(start, count, MAX_WRITE_BITS)?;
+                (start, count, 6 + bit_bytes(count))
+            }
+            Request::WriteMultipleRegisters { start, values } => {
+                let count = values.len() as u16;
+                check_range(start, count, MAX_WRITE_REGISTERS)?;
+                (start, count, 6 + 2 * count as usize)
+            }
+        };
+        if pdu.len() < len {
+            return Err(Error::InvalidRequest);
+        }
+        pdu[0] = self.function();
+        pdu[1..3].copy_from_slice(&first.to_be_bytes());
+        pdu[3..5].copy_from_slice(&second.to_be_bytes());
+        match *self {
+            Request::WriteMultipleCoils { values, .. } => {
+                pdu[5] = (len - 6) as u8;
+                pdu[6..len].fill(0);
+                for (i, _) in values.iter().enumerate().filter(|(_, &value)| value) {
+                    pdu[6 + i / 8] |= 1 << (i % 8);
+                }
+            }
+            Request::WriteMultipleRegisters { values, .. } => {
+                pdu[5] = (len - 6) as u8;
+                for (chunk, value) in pdu[6..len].chunks_exact_mut(2).zip(values) {
+                    chunk.copy_from_slice(&value.to_be_bytes());
+                }
+            }
+            _ => {}
+        }
+        Ok(len)
+    }
+
+    /// Decodes the response `pdu` to this request
+    ///
+    /// Exception responses are returned as [`Error::Exception`].
+    pub fn decode_response<'b>(&self, pdu: &'b [u8]) -> Result<Response<'b>, Error> {
+        let function = self.function();
+        match pdu.first() {
+            Some(&code) if code == function => {}
+            Some(&code) if code == function | 0x80 && pdu.len() == 2 => {
+                return Err(Error::Exception(Exception::from_code(pdu[1])));
+            }
+            _ => return Err(Error::InvalidResponse),
+        }
+        match *self {
+            Request::ReadCoils { count, .. } | Request::ReadDiscreteInputs { count, .. } => {
+                let bytes = bit_bytes(count);
+                if pdu.len() != 2 + bytes || pdu[1] as usize != bytes {
+                    return Err(Error::InvalidResponse);
+                }
+                Ok(Response::Bits(Bits {
+                    data: &pdu[2..],
+                    count,
+                }))
+            }
+            Request::ReadHoldingRegisters { count, .. }
+            | Request::ReadInputRegisters { count, .. } => {
+                let bytes = 2 * count as usize;
+                if pdu.len() != 2 + bytes || pdu[1] as usize != bytes {
+                    return Err(Error::InvalidResponse);
+                }
+                Ok(Response::Registers(Registers { data: &pdu[2..] }))
+            }
+            _ => {
+                // write responses echo the first five bytes of the request
+                let mut request = [0; 6 + 2 * MAX_WRITE_REGISTERS as usize];
+                self.encode(&mut request)?;
+                if pdu.len() != 5 || pdu != &request[..5] {
+                    return Err(Error::InvalidResponse);
+                }
+                Ok(Response::Written)
+            }
+        }
+    }
+}
+
+/// Response to a [Request]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Response<'a> {
+    /// Coils or discrete inputs
+    Bits(Bits<'a>),
+    /// Holding or input registers
+    Registers(Registers<'a>),
+    /// The write was performed
+    Written,
+}
+
+/// Coils or discrete inputs of a response
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bits<'a> {
+    data: &'a [u8],
+    count: u16,
+}
+
+impl<'a> Bits<'a> {
+    /// Number of bits
+    pub fn len(&self) -> usize {
+        self.count as usize
+    }
+
+    /// Returns true if there are no bits
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Bit `index`, counted from the first address of the request
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len() {
+            Some(self.data[index / 8] & (1 << (index % 8)) != 0)
+        } else {
+            None
+        }
+    }
+
+    /// Iterates over the bits
+    pub fn iter(&self) -> impl Iterator<Item = bool> + 'a {
+        let bits = *self;
+        (0..bits.len()).map(move |i| bits.data[i / 8] & (1 << (i % 8)) != 0)
+    }
+}
+
+/// Registers of a response
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Registers<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Registers<'a> {
+    /// Number of registers
+    pub fn len(&self) -> usize {
+        self.data.len() / 2
+    }
+
+    /// Returns true if there are no registers
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Register `index`, counted from the first address of the request
+    pub fn get(&self, index: usize) -> Option<u16> {
+        self.data
+            .get(2 * index..2 * index + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Iterates over the registers
+    pub fn iter(&self) -> impl Iterator<Item = u16> + 'a {
+        self.data
+            .chunks_exact(2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Copies the registers into `buffer`, returns the number of registers copied
+    pub fn copy_to(&self, buffer: &mut [u16]) -> usize {
+        buffer
+            .iter_mut()
+            .zip(self.iter())
+            .map(|(slot, value)| *slot = value)
+            .count()
+    }
+}
+
+/// Coils and registers of a slave
+///
+/// Each method handles one address. The default implementations answer with
+/// [`Exception::IllegalFunction`], addresses that do not exist should be answered with
+/// [`Exception::IllegalDataAddress`].
+pub trait Handler {
+    /// Reads a coil
+    fn read_coil(&mut self, address: u16) -> Result<bool, Exception> {
+        let _ = address;
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Reads a discrete input
+    fn read_discrete_input(&mut self, address: u16) -> Result<bool, Exception> {
+        let _ = address;
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Reads a holding register
+    fn read_holding_register(&mut self, address: u16) -> Result<u16, Exception> {
+        let _ = address;
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Reads an input register
+    fn read_input_register(&mut self, address: u16) -> Result<u16, Exception> {
+        let _ = address;
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Writes a coil
+    fn write_coil(&mut self, address: u16, value: bool) -> Result<(), Exception> {
+        let _ = (address, value);
+        Err(Exception::IllegalFunction)
+    }
+
+    /// Writes a holding register
+    fn write_register(&mut self, address: u16, value: u16) -> Result<(), Exception> {
+        let _ = (address, value);
+        Err(Exception::IllegalFunction)
+    }
+}
+
+/// Answers the request `pdu[..len]` in place and returns the length of the response PDU
+///
+/// `pdu` should hold [MAX_PDU_LEN] bytes for the longest responses. Failed requests are answered
+/// with an exception response. A `len` of 0 returns 0.
+pub fn process_request<H: Handler>(handler: &mut H, pdu: &mut [u8], len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    match handle(handler, pdu, len) {
+        Ok(len) => len,
+        Err(exception) => {
+            pdu[0] |= 0x80;
+            pdu[1] = exception.code();
+            2
+        }
+    }
+}
+
+fn handle<H: Handler>(handler: &mut H, pdu: &mut [u8], len: usize) -> Result<usize, Exception> {
+    let function = pdu[0];
+    if len < 5 {
+        return Err(match function {
+            READ_COILS..=WRITE_SINGLE_REGISTER
+            | WRITE_MULTIPLE_COILS
+            | WRITE_MULTIPLE_REGISTERS => Exception::IllegalDataValue,
+            _ => Exception::IllegalFunction,
+        });
+    }
+    let first = u16::from_be_bytes([pdu[1], pdu[2]]);
+    let second = u16::from_be_bytes([pdu[3], pdu[4]]);
+    match function {
+        READ_COILS | READ_DISCRETE_INPUTS => {
+            check_request(len == 5, first, second, MAX_READ_BITS)?;
+            let bytes = bit_bytes(second);
+            if pdu.len() < 2 + bytes {
+                return Err(Exception::ServerDeviceFailure);
+            }
+            pdu[2..2 + bytes].fill(0);
+            for i in 0..second {
+                let address = first + i;
+                let value = if function == READ_COILS {
+                    handler.read_coil(address)?
+                } else {
+                    handler.read_discrete_input(address)?
+                };
+                if value {
+                    pdu[2 + i as usize / 8] |= 1 << (i % 8);
+                }
+            }
+            pdu[1] = bytes as u8;
+            Ok(2 + bytes)
+        }
+        READ_HOLDING_REGISTERS | READ_INPUT_REGISTERS => {
+            check_request(len == 5, first, second, MAX_READ_REGISTERS)?;
+            let bytes = 2 * second as usize;
+            if pdu.len() < 2 + bytes {
+                return Err(Exception::ServerDeviceFailure);
+            }
+            for i in 0..second {
+                let address = first + i;
+                let value = if function == READ_HOLDING_REGISTERS {
+                    handler.read_holding_register(address)?
+                } else {
+                    handler.read_input_register(address)?
+                };
+                let offset = 2 + 2 * i as usize;
+                pdu[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+            }
+            pdu[1] = bytes as u8;
+            Ok(2 + bytes)
+        }
+        WRITE_SINGLE_COIL => {
+            let value = match second {
+                0xff00 if len == 5 => true,
+                0x0000 if len == 5 => false,
+                _ => return Err(Exception::IllegalDataValue),
+            };
+            handler.write_coil(first, value)?;
+            Ok(5)
+        }
+        WRITE_SINGLE_REGISTER => {
+            if len != 5 {
+                return Err(Exception::IllegalDataValue);
+            }
+            handler.write_register(first, second)?;
+            Ok(5)
+        }
+        WRITE_MULTIPLE_COILS => {
+            let bytes = bit_bytes(second);
+            let valid = len >= 6 && pdu[5] as usize == bytes && len == 6 + bytes;
+            check_request(valid, first, second, MAX_WRITE_BITS)?;
+            for i in 0..second {
+                let value = pdu[6 + i as usize / 8] & (1 << (i % 8)) != 0;
+                handler.write_coil(first + i, value)?;
+            }
+            Ok(5)
+        }
+        WRITE_MULTIPLE_REGISTERS => {
+            let bytes = 2 * second as usize;
+            let valid = len >= 6 && pdu[5] as usize == bytes && len == 6 + bytes;
+            check_request(valid, first, second, MAX_WRITE_REGISTERS)?;
+            for i in 0..second {
+                let offset = 6 + 2 * i as usize;
+                let value = u16::from_be_bytes([pdu[offset], pdu[offset + 1]]);
+                handler.write_register(first + i, value)?;
+            }
+            Ok(5)
+        }
+        _ => Err(Exception::IllegalFunction),
+    }
+}
+
+fn check_request(valid: bool, start: u16, count: u16, max: u16) -> Result<(), Exception> {
+    if !valid || count == 0 || count > max {
+        Err(Exception::IllegalDataValue)
+    } else if start as u32 + count as u32 > 0x1_0000 {
+        Err(Exception::IllegalDataAddress)
+    } else {
+        Ok(())
+    }
+}
+
+fn check_range(start: u16, count: u16, max: u16) -> Result<(), Error> {
+    if count == 0 || count > max || start as u32 + count as u32 > 0x1_0000 {
+        Err(Error::InvalidRequest)
+    } else {
+        Ok(())
+    }
+}
+
+fn bit_bytes(count: u16) -> usize {
+    (count as usize).div_ceil(8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 16 coils and 16 holding registers, the input registers read their address
+    struct Device {
+        coils: [bool; 16],
+        registers: [u16; 16],
+    }
+
+    impl Handler for Device {
+        fn read_coil(&mut self, address: u16) -> Result<bool, Exception> {
+            let coil = self.coils.get(address as usize);
+            coil.copied().ok_or(Exception::IllegalDataAddress)
+        }
+
+        fn read_holding_register(&mut self, address: u16) -> Result<u16, Exception> {
+            let register = self.registers.get(address as usize);
+            register.copied().ok_or(Exception::IllegalDataAddress)
+        }
+
+        fn read_input_register(&mut self, address: u16) -> Result<u16, Exception> {
+            Ok(address)
+        }
+
+        fn write_coil(&mut self, address: u16, value: bool) -> Result<(), Exception> {
+            let coil = self.coils.get_mut(address as usize);
+            *coil.ok_or(Exception::IllegalDataAddress)? = value;
+            Ok(())
+        }
+
+        fn write_register(&mut self, address: u16, value: u16) -> Result<(), Exception> {
+            let register = self.registers.get_mut(address as usize);
+            *register.ok_or(Exception::IllegalDataAddress)? = value;
+            Ok(())
+        }
+    }
+
+    fn device() -> Device {
+        Device {
+            coils: [false; 16],
+            registers: core::array::from_fn(|i| 0x100 + i as u16),
+        }
+    }
+
+    /// Frames `request` for `address`, answers it with `device` and returns the response frame
+    fn exchange(
+        device: &mut Device,
+        address: u8,
+        request: &Request,
+        response: &mut [u8; MAX_ADU_LEN],
+    ) -> usize {
+        let mut frame = [0; MAX_ADU_LEN];
+        frame[0] = address;
+        let len = request.encode(&mut frame[1..]).unwrap();
+        let len = append_crc(&mut frame, 1 + len);
+
+        let (to, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(to, address);
+        response[0] = to;
+        response[1..1 + pdu.len()].copy_from_slice(pdu);
+        let len = process_request(device, &mut response[1..1 + MAX_PDU_LEN], pdu.len());
+        append_crc(response, 1 + len)
+    }
+
+    #[test]
+    fn crc_vectors() {
+        assert_eq!(crc16(&[]), 0xffff);
+        assert_eq!(crc16(&[0x01, 0x03, 0x00, 0x00, 0x00, 0x0a]), 0xcdc5);
+        assert_eq!(crc16(&[0x11, 0x03, 0x00, 0x6b, 0x00, 0x03]), 0x8776);
+        assert_eq!(crc16(&[0x01, 0x06, 0x00, 0x01, 0x00, 0x03]), 0x0b98);
+    }
+
+    #[test]
+    fn framing() {
+        let mut frame = [0; 8];
+        frame[0] = 0x01;
+        let request = Request::ReadHoldingRegisters {
+            start: 0,
+            count: 10,
+        };
+        let len = request.encode(&mut frame[1..]).unwrap();
+        assert_eq!(append_crc(&mut frame, 1 + len), 8);
+        assert_eq!(frame, [0x01, 0x03, 0x00, 0x00, 0x00, 0x0a, 0xc5, 0xcd]);
+
+        let (address, pdu) = decode_frame(&frame).unwrap();
+        assert_eq!(address, 0x01);
+        assert_eq!(pdu, &frame[1..6]);
+
+        frame[7] ^= 1;
+        assert_eq!(decode_frame(&frame), Err(Error::Crc));
+        assert_eq!(decode_frame(&frame[..3]), Err(Error::InvalidFrame));
+    }
+
+    #[test]
+    fn read_registers() {
+        let mut device = device();
+        let mut frame = [0; MAX_ADU_LEN];
+        let request = Request::ReadHoldingRegisters { start: 2, count: 3 };
+        let len = exchange(&mut device, 0x11, &request, &mut frame);
+        assert_eq!(len, 1 + 2 + 6 + 2);
+
+        let (address, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(address, 0x11);
+        let Response::Registers(registers) = request.decode_response(pdu).unwrap() else {
+            panic!("not registers");
+        };
+        assert_eq!(registers.len(), 3);
+        assert!(registers.iter().eq([0x102, 0x103, 0x104]));
+        assert_eq!(registers.get(3), None);
+
+        let request = Request::ReadInputRegisters {
+            start: 0xfffe,
+            count: 2,
+        };
+        let len = exchange(&mut device, 0x11, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        let Response::Registers(registers) = request.decode_response(pdu).unwrap() else {
+            panic!("not registers");
+        };
+        let mut buffer = [0; 4];
+        assert_eq!(registers.copy_to(&mut buffer), 2);
+        assert_eq!(buffer, [0xfffe, 0xffff, 0, 0]);
+    }
+
+    #[test]
+    fn write_and_read_coils() {
+        let mut device = device();
+        let mut frame = [0; MAX_ADU_LEN];
+        let values = [
+            true, false, true, true, false, false, false, false, true, true,
+        ];
+        let request = Request::WriteMultipleCoils {
+            start: 3,
+            values: &values,
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(request.decode_response(pdu), Ok(Response::Written));
+        assert_eq!(device.coils[3..13], values);
+
+        let request = Request::WriteSingleCoil {
+            address: 4,
+            value: true,
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(pdu, [0x05, 0x00, 0x04, 0xff, 0x00]);
+        assert_eq!(request.decode_response(pdu), Ok(Response::Written));
+
+        let request = Request::ReadCoils {
+            start: 3,
+            count: 10,
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(pdu, [0x01, 0x02, 0b0000_1111, 0b0000_0011]);
+        let Response::Bits(bits) = request.decode_response(pdu).unwrap() else {
+            panic!("not bits");
+        };
+        assert_eq!(bits.len(), 10);
+        assert!(bits
+            .iter()
+            .eq([true, true, true, true, false, false, false, false, true, true]));
+        assert_eq!(bits.get(10), None);
+    }
+
+    #[test]
+    fn write_registers() {
+        let mut device = device();
+        let mut frame = [0; MAX_ADU_LEN];
+        let request = Request::WriteMultipleRegisters {
+            start: 14,
+            values: &[0x1234, 0xabcd],
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(pdu, [0x10, 0x00, 0x0e, 0x00, 0x02]);
+        assert_eq!(request.decode_response(pdu), Ok(Response::Written));
+        assert_eq!(device.registers[14..], [0x1234, 0xabcd]);
+
+        let request = Request::WriteSingleRegister {
+            address: 1,
+            value: 3,
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(pdu, [0x06, 0x00, 0x01, 0x00, 0x03]);
+        assert_eq!(request.decode_response(pdu), Ok(Response::Written));
+        assert_eq!(device.registers[1], 3);
+    }
+
+    #[test]
+    fn exceptions() {
+        let mut device = device();
+        let mut frame = [0; MAX_ADU_LEN];
+        let request = Request::ReadHoldingRegisters {
+            start: 15,
+            count: 2,
+        };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(pdu, [0x83, 0x02]);
+        assert_eq!(
+            request.decode_response(pdu),
+            Err(Error::Exception(Exception::IllegalDataAddress))
+        );
+
+        // discrete inputs are not implemented by the handler
+        let request = Request::ReadDiscreteInputs { start: 0, count: 1 };
+        let len = exchange(&mut device, 0x01, &request, &mut frame);
+        let (_, pdu) = decode_frame(&frame[..len]).unwrap();
+        assert_eq!(
+            request.decode_response(pdu),
+            Err(Error::Exception(Exception::IllegalFunction))
+        );
+
+        let mut pdu = [0; MAX_PDU_LEN];
+        pdu[..5].copy_from_slice(&[0x2b, 0x0e, 0x01, 0x00, 0x00]);
+        assert_eq!(process_request(&mut device, &mut pdu, 5), 2);
+        assert_eq!(pdu[..2], [0xab, 0x01]);
+
+        pdu[..5].copy_from_slice(&[0x03, 0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(process_request(&mut device, &mut pdu, 5), 2);
+        assert_eq!(pdu[..2], [0x83, 0x03]);
+
+        pdu[..5].copy_from_slice(&[0x05, 0x00, 0x00, 0x12, 0x34]);
+        assert_eq!(process_request(&mut device, &mut pdu, 5), 2);
+        assert_eq!(pdu[..2], [0x85, 0x03]);
+
+        assert_eq!(process_request(&mut device, &mut pdu, 0), 0);
+    }
+
+    #[test]
+    fn invalid_responses() {
+        let request = Request::ReadHoldingRegisters { start: 0, count: 1 };
+        assert_eq!(
+            request.decode_response(&[0x04, 0x02, 0x00, 0x01]),
+            Err(Error::InvalidResponse)
+        );
+        assert_eq!(
+            request.decode_response(&[0x03, 0x04, 0x00, 0x01, 0x00, 0x02]),
+            Err(Error::InvalidResponse)
+        );
+        assert_eq!(request.decode_response(&[]), Err(Error::InvalidResponse));
+
+        let request = Request::WriteSingleRegister {
+            address: 1,
+            value: 3,
+        };
+        assert_eq!(
+            request.decode_response(&[0x06, 0x00, 0x01, 0x00, 0x04]),
+            Err(Error::InvalidResponse)
+        );
+    }
+
+    #[test]
+    fn request_limits() {
+        let mut pdu = [0; MAX_PDU_LEN];
+        let request = Request::ReadHoldingRegisters {
+            start: 0,
+            count: 126,
+        };
+        assert_eq!(request.encode(&mut pdu), Err(Error::InvalidRequest));
+        let request = Request::ReadCoils {
+            start: 0xffff,
+            count: 2,
+        };
+        assert_eq!(request.encode(&mut pdu), Err(Error::InvalidRequest));
+        let request = Request::ReadCoils { start: 0, count: 0 };
+        assert_eq!(request.encode(&mut pdu), Err(Error::InvalidRequest));
+        let request = Request::WriteMultipleRegisters {
+            start: 0,
+            values: &[0; 123],
+        };
+        assert_eq!(request.encode(&mut pdu), Ok(252));
+        assert_eq!(request.encode(&mut pdu[..251]), Err(Error::InvalidRequest));
+    }
+
+    #[test]
+    fn broadcast() {
+        let mut device = device();
+        let mut frame = [0; MAX_ADU_LEN];
+        let request = Request::WriteSingleRegister {
+            address: 0,
+            value: 0x55aa,
+        };
+        assert!(request.is_write());
+        exchange(&mut device, BROADCAST, &request, &mut frame);
+        assert_eq!(device.registers[0], 0x55aa);
+
+        let request = Request::ReadCoils { start: 0, count: 1 };
+        assert!(!request.is_write());
+    }
+
+    #[test]
+    fn frame_timeouts() {
+        assert_eq!(frame_timeout_us(9_600), 4_011);
+        assert_eq!(frame_timeout_us(19_200), 2_006);
+        assert_eq!(frame_timeout_us(115_200), 1_750);
+    }
+}
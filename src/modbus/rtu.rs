@@ -0,0 +1,256 @@
+//! Modbus RTU over a serial port
+//!
+//! Bytes are gathered until the line is silent for 3.5 characters, measured with a
+//! [`fugit_timer::Timer`] such as a [`Counter`](crate::timer::Counter) at 1 MHz. The port should
+//! be polled at least once per character, so no byte is lost and the timer is restarted in time.
+//!
+//! The port is anything with non-blocking reads and `embedded_io` writes, like a
+//! [`Serial`](crate::serial::Serial) or, on a bus with an RS-485 transceiver, an
+//! [`Rs485`](crate::serial::Rs485) whose flush lowers the driver enable after the last byte.
+
+use super::{
+    append_crc, decode_frame, frame_timeout_us, process_request, Error, Handler, Request, Response,
+    BROADCAST, MAX_ADU_LEN,
+};
+use crate::time::{Bps, MicroSeconds};
+
+use embedded_hal_nb::serial;
+use fugit::TimerDurationU32;
+
+/// Modbus RTU error
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum RtuError<E> {
+    /// The serial port reported an error
+    Serial(E),
+    /// The timer reported an error
+    Timer,
+    /// No response arrived in time
+    Timeout,
+    /// A frame is longer than [MAX_ADU_LEN] bytes
+    FrameTooLong,
+    /// A frame or its PDU is invalid
+    Modbus(Error),
+}
+
+impl<E> From<Error> for RtuError<E> {
+    fn from(err: Error) -> Self {
+        RtuError::Modbus(err)
+    }
+}
+
+struct Rtu<PORT, TIM, const FREQ: u32> {
+    port: PORT,
+    timer: TIM,
+    silence: TimerDurationU32<FREQ>,
+    buffer: [u8; MAX_ADU_LEN],
+    len: usize,
+    overflow: bool,
+}
+
+impl<PORT, TIM, E, const FREQ: u32> Rtu<PORT, TIM, FREQ>
+where
+    PORT: serial::Read<u8, Error = E> + embedded_io::Write<Error = E>,
+    TIM: fugit_timer::Timer<FREQ>,
+{
+    fn new(port: PORT, timer: TIM, baud: Bps) -> Self {
+        Self {
+            port,
+            timer,
+            silence: TimerDurationU32::<FREQ>::micros(frame_timeout_us(baud.0)),
+            buffer: [0; MAX_ADU_LEN],
+            len: 0,
+            overflow: false,
+        }
+    }
+
+    /// Reads the available bytes and returns the length of the frame once the line is silent
+    fn poll(&mut self) -> nb::Result<usize, RtuError<E>> {
+        loop {
+            match self.port.read() {
+                Ok(byte) => {
+                    if self.len < MAX_ADU_LEN {
+                        self.buffer[self.len] = byte;
+                        self.len += 1;
+                    } else {
+                        self.overflow = true;
+                    }
+                    self.timer
+                        .start(self.silence)
+                        .map_err(|_| RtuError::Timer)?;
+                }
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => {
+                    self.reset();
+                    return Err(nb::Error::Other(RtuError::Serial(e)));
+                }
+            }
+        }
+        if self.len == 0 && !self.overflow {
+            return Err(nb::Error::WouldBlock);
+        }
+        match self.timer.wait() {
+            Ok(()) => {
+                let len = self.len;
+                let overflow = self.overflow;
+                self.reset();
+                if overflow {
+                    Err(nb::Error::Other(RtuError::FrameTooLong))
+                } else {
+                    Ok(len)
+                }
+            }
+            Err(nb::Error::WouldBlock) => Err(nb::Error::WouldBlock),
+            Err(nb::Error::Other(_)) => Err(nb::Error::Other(RtuError::Timer)),
+        }
+    }
+
+    /// Waits for a frame, failing with [RtuError::Timeout] if no byte arrives within `timeout`
+    fn receive(&mut self, timeout: TimerDurationU32<FREQ>) -> Result<usize, RtuError<E>> {
+        self.timer.start(timeout).map_err(|_| RtuError::Timer)?;
+        loop {
+            match self.poll() {
+                Ok(len) => return Ok(len),
+                Err(nb::Error::Other(e)) => return Err(e),
+                Err(nb::Error::WouldBlock) => {}
+            }
+            if self.len == 0 {
+                match self.timer.wait() {
+                    Ok(()) => {
+                        self.timer.cancel().ok();
+                        return Err(RtuError::Timeout);
+                    }
+                    Err(nb::Error::WouldBlock) => {}
+                    Err(nb::Error::Other(_)) => return Err(RtuError::Timer),
+                }
+            }
+        }
+    }
+
+    /// Transmits `buffer[..len]` and waits until it is on the line
+    fn send(&mut self, len: usize) -> Result<(), RtuError<E>> {
+        let mut sent = 0;
+        while sent < len {
+            sent += self
+                .port
+                .write(&self.buffer[sent..len])
+                .map_err(RtuError::Serial)?;
+        }
+        self.port.flush().map_err(RtuError::Serial)
+    }
+
+    /// Drops the bytes received so far
+    fn reset(&mut self) {
+        self.timer.cancel().ok();
+        self.len = 0;
+        self.overflow = false;
+    }
+}
+
+/// Modbus RTU master
+pub struct Master<PORT, TIM, const FREQ: u32> {
+    rtu: Rtu<PORT, TIM, FREQ>,
+    timeout: TimerDurationU32<FREQ>,
+}
+
+impl<PORT, TIM, E, const FREQ: u32> Master<PORT, TIM, FREQ>
+where
+    PORT: serial::Read<u8, Error = E> + embedded_io::Write<Error = E>,
+    TIM: fugit_timer::Timer<FREQ>,
+{
+    /// Creates a master on `port` running at `baud`
+    ///
+    /// `timeout` is the time a slave has to start its response.
+    pub fn new(port: PORT, timer: TIM, baud: Bps, timeout: MicroSeconds) -> Self {
+        Self {
+            rtu: Rtu::new(port, timer, baud),
+            timeout: TimerDurationU32::<FREQ>::micros(timeout.ticks()),
+        }
+    }
+
+    /// Sends `request` to the slave at `address` and waits for its response
+    ///
+    /// Requests to [BROADCAST] are not answered and return [`Response::Written`] once they are
+    /// sent, they can only write.
+    pub fn request(&mut self, address: u8, request: &Request) -> Result<Response<'_>, RtuError<E>> {
+        let broadcast = address == BROADCAST;
+        if broadcast && !request.is_write() {
+            return Err(RtuError::Modbus(Error::InvalidRequest));
+        }
+        // drop anything left from a previous transaction
+        while self.rtu.port.read().is_ok() {}
+        self.rtu.reset();
+
+        self.rtu.buffer[0] = address;
+        let len = request.encode(&mut self.rtu.buffer[1..MAX_ADU_LEN - 2])?;
+        let len = append_crc(&mut self.rtu.buffer, 1 + len);
+        self.rtu.send(len)?;
+        if broadcast {
+            return Ok(Response::Written);
+        }
+
+        let len = self.rtu.receive(self.timeout)?;
+        let (from, pdu) = decode_frame(&self.rtu.buffer[..len])?;
+        if from != address {
+            return Err(RtuError::Modbus(Error::InvalidResponse));
+        }
+        Ok(request.decode_response(pdu)?)
+    }
+
+    /// Returns the port and the timer
+    pub fn release(self) -> (PORT, TIM) {
+        (self.rtu.port, self.rtu.timer)
+    }
+}
+
+/// Modbus RTU slave
+pub struct Slave<PORT, TIM, const FREQ: u32> {
+    rtu: Rtu<PORT, TIM, FREQ>,
+    address: u8,
+}
+
+impl<PORT, TIM, E, const FREQ: u32> Slave<PORT, TIM, FREQ>
+where
+    PORT: serial::Read<u8, Error = E> + embedded_io::Write<Error = E>,
+    TIM: fugit_timer::Timer<FREQ>,
+{
+    /// Creates a slave answering to `address` on `port` running at `baud`
+    pub fn new(port: PORT, timer: TIM, baud: Bps, address: u8) -> Self {
+        Self {
+            rtu: Rtu::new(port, timer, baud),
+            address,
+        }
+    }
+
+    /// The address of the slave
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Receives requests and answers them with `handler`, non-blocking
+    ///
+    /// Returns `Ok` once a request to this slave or a broadcast is processed. Frames for other
+    /// slaves are dropped. Broadcasts are processed without a response.
+    pub fn poll<H: Handler>(&mut self, handler: &mut H) -> nb::Result<(), RtuError<E>> {
+        let len = self.rtu.poll()?;
+        let (address, pdu_len) = match decode_frame(&self.rtu.buffer[..len]) {
+            Ok((address, pdu)) => (address, pdu.len()),
+            Err(err) => return Err(nb::Error::Other(err.into())),
+        };
+        if address != self.address && address != BROADCAST {
+            return Err(nb::Error::WouldBlock);
+        }
+        let len = process_request(handler, &mut self.rtu.buffer[1..MAX_ADU_LEN - 2], pdu_len);
+        if address != BROADCAST {
+            let len = append_crc(&mut self.rtu.buffer, 1 + len);
+            self.rtu.send(len)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the port and the timer
+    pub fn release(self) -> (PORT, TIM) {
+        (self.rtu.port, self.rtu.timer)
+    }
+}
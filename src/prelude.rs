@@ -9,13 +9,16 @@ pub use crate::dma::ReadDma as _py32f0xx_hal_dma_ReadDma;
 pub use crate::dma::ReadWriteDma as _py32f0xx_hal_dma_ReadWriteDma;
 #[cfg(feature = "with-dma")]
 pub use crate::dma::WriteDma as _py32f0xx_hal_dma_WriteDma;
+pub use crate::exti::ExtiExt as _py32f0xx_hal_exti_ExtiExt;
 pub use crate::gpio::GpioExt as _py32f0xx_hal_gpio_GpioExt;
 pub use crate::rcc::RccExt as _py32f0xx_hal_rcc_RccExt;
 pub use crate::serial::SerialExt as _py32f0xx_hal_serial_SerialExt;
+#[cfg(not(feature = "py32f072"))]
 pub use crate::spi::SpiExt as _py32f0xx_hal_spi_SpiExt;
 pub use crate::time::U32Ext as _py32f0xx_hal_time_U32Ext;
 #[cfg(feature = "rtic")]
 pub use crate::timer::monotonic::MonoTimerExt as _py32f0xx_hal_timer_monotonic_MonoTimerExt;
+#[cfg(not(feature = "py32f072"))]
 pub use crate::timer::pwm::PwmExt as _py32f0xx_hal_timer_pwm_PwmExt;
 pub use crate::timer::SysTimerExt as _py32f0xx_hal_timer_SysTimerExt;
 pub use crate::timer::TimerExt as _py32f0xx_hal_timer_TimerExt;
@@ -95,7 +95,7 @@ pub enum MCOSrc {
     Pll = 5,
     ///6: LSI oscillator clock selected
     Lsi = 6,
-    #[cfg(feature = "py32f030")]
+    #[cfg(any(feature = "py32f030", feature = "py32f072"))]
     ///7: LSE oscillator clock selected
     Lse = 7,
 }
@@ -109,7 +109,7 @@ impl From<MCOSrc> for MCOSEL_A {
             MCOSrc::Hse => MCOSEL_A::Hse,
             MCOSrc::Pll => MCOSEL_A::Pll,
             MCOSrc::Lsi => MCOSEL_A::Lsi,
-            #[cfg(feature = "py32f030")]
+            #[cfg(any(feature = "py32f030", feature = "py32f072"))]
             MCOSrc::Lse => MCOSEL_A::Lse,
         }
     }
@@ -290,6 +290,22 @@ mod inner {
         while !rcc.cr.read().hserdy().bit_is_set() {}
     }
 
+    #[cfg(feature = "py32f072")]
+    fn hse_enable(rcc: &mut RCC, bypassed: &HSEBypassMode) {
+        // PY32F072 has no HSE frequency range, the reset drive level covers all crystals
+        match bypassed {
+            super::HSEBypassMode::NotBypassed => {
+                rcc.cr
+                    .modify(|_, w| w.csson().on().hseon().on().hsebyp().not_bypassed());
+            }
+            super::HSEBypassMode::Bypassed => {
+                rcc.cr
+                    .modify(|_, w| w.csson().on().hseon().on().hsebyp().bypassed());
+            }
+        }
+        while !rcc.cr.read().hserdy().bit_is_set() {}
+    }
+
     #[cfg(all(feature = "py32f002b", not(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))))]
     fn hse_enable(rcc: &mut RCC) {
         // PY32F002B HSE only support the bypass mode
@@ -311,6 +327,8 @@ mod inner {
             SysClkSource::HSE(freq, bypassed) => {
                 #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
                 hse_enable(rcc, *freq, bypassed);
+                #[cfg(feature = "py32f072")]
+                hse_enable(rcc, bypassed);
                 #[cfg(feature = "py32f002b")]
                 if let HSEBypassMode::Bypassed = bypassed {
                     hse_enable(rcc);
@@ -325,7 +343,13 @@ mod inner {
     }
 
     #[cfg(feature = "py32f030")]
-    pub(super) fn enable_pll(rcc: &mut RCC, c_src: &SysClkSource, ppre_bits: u8, hpre_bits: u8) {
+    pub(super) fn enable_pll(
+        rcc: &mut RCC,
+        c_src: &SysClkSource,
+        _pll_mul: u32,
+        ppre_bits: u8,
+        hpre_bits: u8,
+    ) {
         let pllsrc_bit = match c_src {
             SysClkSource::HSISYS(_) => false,
             SysClkSource::HSE(_, _) => true,
@@ -339,15 +363,44 @@ mod inner {
             .modify(|_, w| unsafe { w.ppre().bits(ppre_bits).hpre().bits(hpre_bits).sw().pll() });
     }
 
-    #[cfg(not(feature = "py32f030"))]
+    #[cfg(feature = "py32f072")]
+    pub(super) fn enable_pll(
+        rcc: &mut RCC,
+        c_src: &SysClkSource,
+        pll_mul: u32,
+        ppre_bits: u8,
+        hpre_bits: u8,
+    ) {
+        let pllsrc_bits = match c_src {
+            SysClkSource::HSISYS(_) => 0b00,
+            SysClkSource::HSE(_, _) => 0b01,
+        };
+        let pllmul_bits = match pll_mul {
+            2 => 0b00,
+            3 => 0b01,
+            _ => unreachable!(),
+        };
+        rcc.pllcfgr.modify(|_, w| unsafe {
+            w.pllsrc().bits(pllsrc_bits).pllmul().bits(pllmul_bits)
+        });
+
+        rcc.cr.modify(|_, w| w.pllon().set_bit());
+        while rcc.cr.read().pllrdy().bit_is_clear() {}
+
+        rcc.cfgr
+            .modify(|_, w| unsafe { w.ppre().bits(ppre_bits).hpre().bits(hpre_bits).sw().pll() });
+    }
+
+    #[cfg(not(any(feature = "py32f030", feature = "py32f072")))]
     #[inline(always)]
     pub(super) fn enable_pll(
         _rcc: &mut RCC,
         _c_src: &SysClkSource,
+        _pll_mul: u32,
         _ppre_bits: u8,
         _hpre_bits: u8,
     ) {
-        panic!("PLL only supported on py32f030. Please select a sysclk and clock source combination so as to not require the use of PLL")
+        panic!("PLL only supported on py32f030 and py32f072. Please select a sysclk and clock source combination so as to not require the use of PLL")
     }
 
     pub(super) fn get_sww(c_src: &SysClkSource) -> SW_A {
@@ -478,7 +531,12 @@ pub struct CFGR {
 
 impl CFGR {
     /// use the HSE as system clock
-    #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+    #[cfg(any(
+        feature = "py32f030",
+        feature = "py32f003",
+        feature = "py32f002a",
+        feature = "py32f072"
+    ))]
     pub fn hse<F>(mut self, freq: F, bypass: HSEBypassMode) -> Self
     where
         F: Into<Hertz>,
@@ -539,10 +597,12 @@ impl CFGR {
         // Highest selected frequency source available takes precedent.
         let src_clk_freq = self::inner::get_freq(&self.clock_src);
 
-        let (pll_en, hsi_div_bits) = if sysclk == src_clk_freq {
-            (false, None)
+        let (pll_mul, hsi_div_bits) = if sysclk == src_clk_freq {
+            (None, None)
         } else if sysclk == src_clk_freq * 2 {
-            (true, None)
+            (Some(2), None)
+        } else if cfg!(feature = "py32f072") && sysclk == src_clk_freq * 3 {
+            (Some(3), None)
         } else {
             if let SysClkSource::HSISYS(_) = self.clock_src {
                 let div = match src_clk_freq / sysclk {
@@ -556,7 +616,7 @@ impl CFGR {
                     48..=95 => 0b110,
                     _ => 0b111,
                 };
-                (false, Some(div))
+                (None, Some(div))
             } else {
                 unreachable!()
             }
@@ -604,6 +664,7 @@ impl CFGR {
         let pclk = hclk / cast::u32(ppre);
 
         // adjust flash wait states
+        #[cfg(not(feature = "py32f072"))]
         flash.acr.write(|w| {
             if r_sysclk <= 24_000_000 {
                 w.latency().ws0()
@@ -611,13 +672,29 @@ impl CFGR {
                 w.latency().ws1()
             }
         });
+        #[cfg(feature = "py32f072")]
+        flash.acr.write(|w| {
+            if r_sysclk <= 24_000_000 {
+                w.latency().ws0()
+            } else if r_sysclk <= 48_000_000 {
+                w.latency().ws1()
+            } else {
+                w.latency().ws2()
+            }
+        });
 
         // Enable the requested clock
         self::inner::enable_clock(&mut self.rcc, &self.clock_src);
 
         // Enable PLL
-        if pll_en {
-            self::inner::enable_pll(&mut self.rcc, &self.clock_src, ppre_bits, hpre_bits);
+        if let Some(pll_mul) = pll_mul {
+            self::inner::enable_pll(
+                &mut self.rcc,
+                &self.clock_src,
+                pll_mul,
+                ppre_bits,
+                hpre_bits,
+            );
         } else {
             let sw_var = self::inner::get_sww(&self.clock_src);
 
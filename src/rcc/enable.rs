@@ -36,12 +36,9 @@ macro_rules! bus {
 }
 
 bus! {
-    ADC => (APB, apbenr2, apbrstr2, 20),
     CRC => (AHB, ahbenr, ahbrstr, 12),
-    DBG => (APB, apbenr1, apbrstr1, 27),
     GPIOA => (APB, iopenr, ioprstr, 0),
     GPIOB => (APB, iopenr, ioprstr, 1),
-    I2C => (APB, apbenr1, apbrstr1, 21),
     PWR => (APB, apbenr1, apbrstr1, 28),
     SPI1 => (APB, apbenr2, apbrstr2, 12),
     SYSCFG => (APB, apbenr2, apbrstr2, 0),
@@ -49,25 +46,36 @@ bus! {
     USART1 => (APB, apbenr2, apbrstr2, 14),
 }
 
-#[cfg(any(feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f002a", feature = "py32f002b", feature = "py32f003", feature = "py32f030"))]
+bus! {
+    ADC => (APB, apbenr2, apbrstr2, 20),
+    DBG => (APB, apbenr1, apbrstr1, 27),
+    I2C => (APB, apbenr1, apbrstr1, 21),
+}
+
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 bus! {
     USART2 => (APB, apbenr1, apbrstr1, 17),
     DMA => (AHB, ahbenr, ahbrstr, 0),
     WWDG => (APB, apbenr1, apbrstr1, 11),
 }
 
-#[cfg(any(feature = "py32f030"))]
+#[cfg(any(feature = "py32f030", feature = "py32f072"))]
 bus! {
     SPI2 => (APB, apbenr1, apbrstr1, 14),
 }
 
-#[cfg(any(feature = "py32f002a", feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f002a", feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 bus! {
     GPIOF => (APB, iopenr, ioprstr, 5),
+}
+
+#[cfg(any(feature = "py32f002a", feature = "py32f003", feature = "py32f030"))]
+bus! {
     LPTIM => (APB, apbenr1, apbrstr1, 31),
 }
 
-#[cfg(any(feature = "py32f002b"))]
+#[cfg(any(feature = "py32f002b", feature = "py32f072"))]
 bus! {
     GPIOC => (APB, iopenr, ioprstr, 2),
     LPTIM1 => (APB, apbenr1, apbrstr1, 31),
@@ -78,18 +86,36 @@ bus! {
     LED => (APB, apbenr2, apbrstr2, 23),
 }
 
-#[cfg(any(feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 bus! {
     TIM3 => (APB, apbenr1, apbrstr1, 1),
     TIM17 => (APB, apbenr2, apbrstr2, 18),
 }
 
-#[cfg(any(feature = "py32f002b", feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f002b", feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 bus! {
     TIM14 => (APB, apbenr2, apbrstr2, 15),
 }
 
-#[cfg(any(feature = "py32f002a", feature = "py32f003", feature = "py32f030"))]
+#[cfg(any(feature = "py32f002a", feature = "py32f003", feature = "py32f030", feature = "py32f072"))]
 bus! {
     TIM16 => (APB, apbenr2, apbrstr2, 17),
 }
+
+#[cfg(feature = "py32f072")]
+bus! {
+    ADC => (APB, apbenr2, apbrstr2, 9),
+    CAN => (APB, apbenr1, apbrstr1, 25),
+    DAC => (APB, apbenr1, apbrstr1, 29),
+    DBG => (APB, apbenr2, apbrstr2, 10),
+    I2C1 => (APB, apbenr1, apbrstr1, 21),
+    I2C2 => (APB, apbenr1, apbrstr1, 22),
+    LCD => (APB, apbenr2, apbrstr2, 23),
+    TIM2 => (APB, apbenr1, apbrstr1, 0),
+    TIM6 => (APB, apbenr1, apbrstr1, 4),
+    TIM7 => (APB, apbenr1, apbrstr1, 5),
+    TIM15 => (APB, apbenr2, apbrstr2, 16),
+    USART3 => (APB, apbenr1, apbrstr1, 18),
+    USART4 => (APB, apbenr1, apbrstr1, 19),
+    USB => (APB, apbenr1, apbrstr1, 23),
+}
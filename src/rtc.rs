@@ -9,14 +9,14 @@ use core::convert::Infallible;
 use core::marker::PhantomData;
 
 // The LSE runs at at 32 768 hertz unless an external clock is provided
-#[cfg(feature = "py32f030")]
+#[cfg(any(feature = "py32f030", feature = "py32f072"))]
 const LSE_HERTZ: Hertz = Hz(32_768);
 const LSI_HERTZ: Hertz = Hz(32_768);
 
 /// RTC clock source HSE clock divided by 128 (type state)
 pub struct RtcClkHseDiv128;
 /// RTC clock source LSE oscillator clock (type state)
-#[cfg(feature = "py32f030")]
+#[cfg(any(feature = "py32f030", feature = "py32f072"))]
 pub struct RtcClkLse;
 /// RTC clock source LSI oscillator clock (type state)
 pub struct RtcClkLsi;
@@ -51,7 +51,7 @@ pub struct Rtc<CS = RtcClkLsi> {
     _clock_source: PhantomData<CS>,
 }
 
-#[cfg(feature = "py32f030")]
+#[cfg(any(feature = "py32f030", feature = "py32f072"))]
 impl Rtc<RtcClkLse> {
     /**
       Initialises the RTC with low-speed external crystal source (lse).
@@ -306,8 +306,10 @@ impl<CS> Rtc<CS> {
         self.perform_write(|s| {
             s.regs
                 .alrh
-                .write(|w| w.alrh().bits((alarm_value >> 16) as u16));
-            s.regs.alrl.write(|w| w.alrl().bits(alarm_value as u16));
+                .write(|w| unsafe { w.bits(alarm_value >> 16) });
+            s.regs
+                .alrl
+                .write(|w| unsafe { w.bits(alarm_value as u16 as u32) });
         });
 
         self.clear_alarm_flag();
@@ -23,7 +23,7 @@
 //! let rx = gpiob.pb7.into_alternate_af0();
 //!
 //! // Create an interface struct for USART1 with 115200 Baud
-//! let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks);
+//! let mut serial = p.USART1.serial((tx, rx), 115_200.bps(), &rcc.clocks).unwrap();
 //!
 //! loop {
 //!     let received = block!(serial.read()).unwrap();
@@ -47,7 +47,7 @@
 //!
 //! let tx = gpiob.pb6.into_alternate_af0();
 //!
-//! let mut serial = p.USART1.tx(tx, 115_200.bps(), &rcc.clocks);
+//! let mut serial = p.USART1.tx(tx, 115_200.bps(), &rcc.clocks).unwrap();
 //!
 //! loop {
 //!     serial.write_str("Hello World!\r\n");
@@ -62,131 +62,142 @@ use crate::dma::{
     self, Ch, CircBuffer, DmaExt, PeriphMap, Receive, RxDma, Transfer, TransferPayload, Transmit,
     TxDma,
 };
-use crate::gpio::{gpioa::*, gpiob::*};
-use crate::gpio::{Alternate, AF1};
+use crate::gpio::{af_pins, Alternate, Pin};
 use crate::pac::{self, RCC};
 use crate::rcc::{BusClock, Clocks, Enable, Reset};
-use crate::time::{Bps, U32Ext};
+use crate::time::{Bps, Hertz, U32Ext};
 #[cfg(feature = "with-dma")]
 use core::sync::atomic::{self, Ordering};
 #[cfg(feature = "with-dma")]
 use embedded_dma::{ReadBuffer, WriteBuffer};
 
-#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
-use crate::gpio::{gpiof::*, AF0, AF8};
-
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
-use crate::gpio::{AF3, AF4, AF9};
-
-#[cfg(feature = "py32f002b")]
-use crate::gpio::AF3;
+pub use crate::baud::BaudCalc;
 
+mod buffered;
+pub use buffered::{BufferedInterrupt, BufferedSerial, RingBuffer};
+mod half_duplex;
+pub use half_duplex::HalfDuplex;
 mod hal_02;
 mod hal_1;
+mod hal_async;
+pub use hal_async::on_interrupt;
+mod irda;
+pub use irda::{Irda, IrdaMode};
+#[cfg(feature = "py32f072")]
+mod lin;
+#[cfg(feature = "py32f072")]
+pub use lin::{BreakLength, Lin, LinError};
+mod multiprocessor;
+pub use multiprocessor::{AddressedWord, WakeUp};
+mod rs485;
+pub use rs485::{DriverEnable, Rs485, Rs485Config};
+#[cfg(feature = "py32f072")]
+mod smartcard;
+#[cfg(feature = "py32f072")]
+pub use smartcard::{Smartcard, SmartcardConfig};
+#[cfg(not(feature = "py32f072"))]
+mod synchronous;
+#[cfg(not(feature = "py32f072"))]
+pub use synchronous::Synchronous;
+#[cfg(feature = "with-dma")]
+mod frame;
+#[cfg(feature = "with-dma")]
+pub use frame::{Frame, FrameReader};
 
 /// Trait for Serial Transmit pin
 pub trait TxPin<USART> {}
 /// Trait for Serial Receive pin
 pub trait RxPin<USART> {}
+/// Trait for Serial Clock pin of the synchronous mode
+pub trait CkPin<USART> {}
+/// Trait for Serial Request To Send pin
+pub trait RtsPin<USART> {}
+/// Trait for Serial Clear To Send pin
+pub trait CtsPin<USART> {}
 
-/// Macro to implement `TxPin` / `RxPin` for a certain pin, using a certain
-/// alternative function and for a certain serial peripheral.
-macro_rules! impl_pins {
-    ($($pin:ident, $af:ident, $instance:ident, $trait:ident;)*) => {
-        $(
-            impl $trait<crate::pac::$instance> for $pin<Alternate<$af>> {}
-        )*
+// afgen:begin serial
+// Generated by tools/afgen.py from tools/af/*.csv, do not edit by hand
+
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002a",
+    feature = "py32f002b"
+))]
+af_pins! {
+    USART1 => {
+        RtsPin => [PA1<AF1>],
+        CtsPin => [PA0<AF1>],
+    }
+}
+
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
+af_pins! {
+    USART1 => {
+        TxPin => [PA2<AF1>, PA7<AF8>, PA14<AF1>, PB6<AF0>, PF1<AF8>],
+        RxPin => [PA3<AF1>, PA13<AF8>, PB2<AF0>, PF0<AF8>],
+        CkPin => [PA4<AF1>],
+        RtsPin => [PA12<AF1>],
     }
 }
 
 #[cfg(any(feature = "py32f030", feature = "py32f003"))]
-impl_pins!(
-    PA0, AF9, USART2, TxPin;
-    PA1, AF9, USART2, RxPin;
-    PA2, AF1, USART1, TxPin;
-    PA2, AF4, USART2, TxPin;
-    PA3, AF1, USART1, RxPin;
-    PA3, AF4, USART2, RxPin;
-    PA4, AF9, USART2, TxPin;
-    PA5, AF9, USART2, RxPin;
-    PA7, AF8, USART1, TxPin;
-    PA7, AF9, USART2, TxPin;
-    PA13, AF8, USART1, RxPin;
-    PA14, AF1, USART1, TxPin;
-    PA14, AF4, USART2, TxPin;
-
-    PB2, AF0, USART1, RxPin;
-    PB2, AF3, USART2, RxPin;
-    PB6, AF0, USART1, TxPin;
-    PB6, AF4, USART2, TxPin;
-    PB7, AF0, USART1, RxPin;
-    PB7, AF4, USART2, RxPin;
-
-    PF0, AF4, USART2, RxPin;
-    PF0, AF8, USART1, RxPin;
-    PF0, AF9, USART2, TxPin;
-    PF1, AF4, USART2, TxPin;
-    PF1, AF8, USART1, TxPin;
-    PF1, AF9, USART2, RxPin;
-    PF2, AF4, USART2, RxPin;
-    PF3, AF0, USART1, TxPin;
-    PF3, AF4, USART2, TxPin;
-);
+af_pins! {
+    USART1 => {
+        TxPin => [PF3<AF0>],
+        RxPin => [PB7<AF0>],
+    }
+    USART2 => {
+        TxPin => [
+            PA0<AF9>,
+            PA2<AF4>,
+            PA4<AF9>,
+            PA7<AF9>,
+            PA14<AF4>,
+            PB6<AF4>,
+            PF0<AF9>,
+            PF1<AF4>,
+            PF3<AF4>,
+        ],
+        RxPin => [PA1<AF9>, PA3<AF4>, PA5<AF9>, PB2<AF3>, PB7<AF4>, PF0<AF4>, PF1<AF9>, PF2<AF4>],
+        RtsPin => [PA1<AF4>, PA12<AF4>],
+        CtsPin => [PA0<AF4>],
+    }
+}
+
+#[cfg(any(feature = "py32f030", feature = "py32f002a"))]
+af_pins! {
+    USART1 => {
+        TxPin => [PA9<AF1>, PA10<AF8>],
+        RxPin => [PA8<AF8>, PA9<AF8>, PA10<AF1>],
+        CkPin => [PA8<AF1>],
+        CtsPin => [PA11<AF1>],
+    }
+}
 
 #[cfg(feature = "py32f030")]
-impl_pins!(
-    PA8, AF8, USART1, RxPin;
-    PA8, AF9, USART2, RxPin;
-    PA9, AF1, USART1, TxPin;
-    PA9, AF4, USART2, TxPin;
-    PA9, AF8, USART1, RxPin;
-    PA10, AF1, USART1, RxPin;
-    PA10, AF4, USART2, RxPin;
-    PA10, AF8, USART1, TxPin;
-    PA15, AF1, USART1, RxPin;
-    PA15, AF4, USART2, RxPin;
-
-    PB8, AF4, USART2, TxPin;
-    PB8, AF8, USART1, TxPin;
-);
-
-#[cfg(feature = "py32f002a")]
-impl_pins!(
-    PA2, AF1, USART1, TxPin;
-    PA3, AF1, USART1, RxPin;
-
-    PA7, AF8, USART1, TxPin;
-    PA8, AF8, USART1, RxPin;
-
-    PA9, AF1, USART1, TxPin;
-    PA9, AF8, USART1, RxPin;
-
-    PA10, AF1, USART1, RxPin;
-    PA10, AF8, USART1, TxPin;
-
-    PA13, AF8, USART1, RxPin;
-    PA14, AF1, USART1, TxPin;
-
-    PB2, AF0, USART1, RxPin;
-    PB6, AF0, USART1, TxPin;
-
-    PF0, AF8, USART1, RxPin;
-    PF1, AF8, USART1, TxPin;
-);
+af_pins! {
+    USART1 => {
+        TxPin => [PB8<AF8>],
+        RxPin => [PA15<AF1>],
+    }
+    USART2 => {
+        TxPin => [PA9<AF4>, PB8<AF4>],
+        RxPin => [PA8<AF9>, PA10<AF4>, PA15<AF4>],
+        CkPin => [PA8<AF4>],
+        CtsPin => [PA11<AF4>],
+    }
+}
 
 #[cfg(feature = "py32f002b")]
-impl_pins!(
-    PA2, AF1, USART1, RxPin;
-    PA3, AF1, USART1, TxPin;
-    PA4, AF1, USART1, RxPin;
-    PA6, AF1, USART1, TxPin;
-    PA7, AF1, USART1, TxPin;
-    PA7, AF3, USART1, RxPin;
-
-    PB4, AF1, USART1, TxPin;
-    PB5, AF1, USART1, RxPin;
-    PB6, AF1, USART1, TxPin;
-);
+af_pins! {
+    USART1 => {
+        TxPin => [PA3<AF1>, PA6<AF1>, PA7<AF1>, PB4<AF1>, PB6<AF1>],
+        RxPin => [PA2<AF1>, PA4<AF1>, PA7<AF3>, PB5<AF1>],
+        CkPin => [PA5<AF1>],
+    }
+}
+// afgen:end serial
 
 /// Extension trait for USART peripherals
 pub trait SerialExt: Sized + Instance {
@@ -196,11 +207,78 @@ pub trait SerialExt: Sized + Instance {
         pins: (TXPIN, RXPIN),
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Serial<Self, TXPIN, RXPIN>;
+    ) -> Result<Serial<Self, TXPIN, RXPIN>, ConfigError>;
     /// Initialize a [Tx]-only Serial
-    fn tx<TXPIN>(self, tx_pin: TXPIN, config: impl Into<Config>, clocks: &Clocks) -> Tx<Self>;
+    fn tx<TXPIN>(
+        self,
+        tx_pin: TXPIN,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Tx<Self>, ConfigError>;
     /// Initialize a [Rx]-only Serial
-    fn rx<RXPIN>(self, rx_pin: RXPIN, config: impl Into<Config>, clocks: &Clocks) -> Rx<Self>;
+    fn rx<RXPIN>(
+        self,
+        rx_pin: RXPIN,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Rx<Self>, ConfigError>;
+    /// Initialize a [Serial] with hardware flow control on the RTS and CTS pins
+    #[allow(clippy::type_complexity)]
+    fn with_flow_control<TXPIN, RXPIN, RTSPIN, CTSPIN>(
+        self,
+        pins: (TXPIN, RXPIN),
+        flow: (RTSPIN, CTSPIN),
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Serial<Self, (TXPIN, RTSPIN), (RXPIN, CTSPIN)>, ConfigError>
+    where
+        RTSPIN: RtsPin<Self>,
+        CTSPIN: CtsPin<Self>;
+    /// Initialize a single wire [HalfDuplex] Serial on the TX pin
+    fn half_duplex<const P: char, const N: u8, AF>(
+        self,
+        pin: Pin<P, N, Alternate<AF>>,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<HalfDuplex<Self, Pin<P, N, Alternate<AF>>>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<Self>;
+    /// Initialize a [Synchronous] Serial, an SPI master on the clock, RX and TX pins
+    #[cfg(not(feature = "py32f072"))]
+    fn synchronous<CKPIN, RXPIN, TXPIN>(
+        self,
+        pins: (CKPIN, RXPIN, TXPIN),
+        mode: crate::spi::Mode,
+        freq: Hertz,
+        clocks: &Clocks,
+    ) -> Result<Synchronous<Self, CKPIN, RXPIN, TXPIN>, ConfigError>
+    where
+        CKPIN: CkPin<Self>,
+        RXPIN: RxPin<Self>,
+        TXPIN: TxPin<Self>;
+    /// Initialize an [Irda] Serial, an IrDA SIR encoder on the TX pin and decoder on the RX pin
+    fn irda<TXPIN, RXPIN>(
+        self,
+        pins: (TXPIN, RXPIN),
+        mode: IrdaMode,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Irda<Self, TXPIN, RXPIN>, ConfigError>
+    where
+        TXPIN: TxPin<Self>,
+        RXPIN: RxPin<Self>;
+    /// Initialize a [Smartcard] Serial with the card I/O on the TX pin and its clock on the CK
+    /// pin
+    #[cfg(feature = "py32f072")]
+    fn smartcard<const P: char, const N: u8, AF, CKPIN>(
+        self,
+        pins: (Pin<P, N, Alternate<AF>>, CKPIN),
+        config: SmartcardConfig,
+        clocks: &Clocks,
+    ) -> Result<Smartcard<Self, Pin<P, N, Alternate<AF>>, CKPIN>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<Self>,
+        CKPIN: CkPin<Self>;
 }
 
 impl<USART: Instance> SerialExt for USART {
@@ -209,14 +287,89 @@ impl<USART: Instance> SerialExt for USART {
         pins: (TXPIN, RXPIN),
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Serial<Self, TXPIN, RXPIN> {
+    ) -> Result<Serial<Self, TXPIN, RXPIN>, ConfigError> {
         Serial::new(self, pins, config, clocks)
     }
-    fn tx<TXPIN>(self, tx_pin: TXPIN, config: impl Into<Config>, clocks: &Clocks) -> Tx<Self> {
-        Serial::_new(self, (tx_pin, ()), config, clocks).split().0
+    fn tx<TXPIN>(
+        self,
+        tx_pin: TXPIN,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Tx<Self>, ConfigError> {
+        Serial::tx(self, tx_pin, config, clocks)
+    }
+    fn rx<RXPIN>(
+        self,
+        rx_pin: RXPIN,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Rx<Self>, ConfigError> {
+        Serial::rx(self, rx_pin, config, clocks)
+    }
+    fn with_flow_control<TXPIN, RXPIN, RTSPIN, CTSPIN>(
+        self,
+        pins: (TXPIN, RXPIN),
+        flow: (RTSPIN, CTSPIN),
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Serial<Self, (TXPIN, RTSPIN), (RXPIN, CTSPIN)>, ConfigError>
+    where
+        RTSPIN: RtsPin<Self>,
+        CTSPIN: CtsPin<Self>,
+    {
+        Serial::with_flow_control(self, pins, flow, config, clocks)
+    }
+    fn half_duplex<const P: char, const N: u8, AF>(
+        self,
+        pin: Pin<P, N, Alternate<AF>>,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<HalfDuplex<Self, Pin<P, N, Alternate<AF>>>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<Self>,
+    {
+        Serial::half_duplex(self, pin, config, clocks)
+    }
+    #[cfg(not(feature = "py32f072"))]
+    fn synchronous<CKPIN, RXPIN, TXPIN>(
+        self,
+        pins: (CKPIN, RXPIN, TXPIN),
+        mode: crate::spi::Mode,
+        freq: Hertz,
+        clocks: &Clocks,
+    ) -> Result<Synchronous<Self, CKPIN, RXPIN, TXPIN>, ConfigError>
+    where
+        CKPIN: CkPin<Self>,
+        RXPIN: RxPin<Self>,
+        TXPIN: TxPin<Self>,
+    {
+        Serial::synchronous(self, pins, mode, freq, clocks)
     }
-    fn rx<RXPIN>(self, rx_pin: RXPIN, config: impl Into<Config>, clocks: &Clocks) -> Rx<Self> {
-        Serial::_new(self, ((), rx_pin), config, clocks).split().1
+    fn irda<TXPIN, RXPIN>(
+        self,
+        pins: (TXPIN, RXPIN),
+        mode: IrdaMode,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Irda<Self, TXPIN, RXPIN>, ConfigError>
+    where
+        TXPIN: TxPin<Self>,
+        RXPIN: RxPin<Self>,
+    {
+        Serial::irda(self, pins, mode, config, clocks)
+    }
+    #[cfg(feature = "py32f072")]
+    fn smartcard<const P: char, const N: u8, AF, CKPIN>(
+        self,
+        pins: (Pin<P, N, Alternate<AF>>, CKPIN),
+        config: SmartcardConfig,
+        clocks: &Clocks,
+    ) -> Result<Smartcard<Self, Pin<P, N, Alternate<AF>>, CKPIN>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<Self>,
+        CKPIN: CkPin<Self>,
+    {
+        Serial::smartcard(self, pins, config, clocks)
     }
 }
 
@@ -228,6 +381,8 @@ pub trait Instance:
 {
     #[doc(hidden)]
     fn ptr() -> *const uart_base::RegisterBlock;
+    #[doc(hidden)]
+    fn state() -> &'static hal_async::State;
 }
 
 macro_rules! inst {
@@ -237,6 +392,10 @@ macro_rules! inst {
                 fn ptr() -> *const uart_base::RegisterBlock {
                     <$USARTX>::ptr()
                 }
+                fn state() -> &'static hal_async::State {
+                    static STATE: hal_async::State = hal_async::State::new();
+                    &STATE
+                }
             }
         )+
     };
@@ -247,16 +406,23 @@ macro_rules! inst {
     feature = "py32f002b",
     feature = "py32f003",
     feature = "py32f030",
+    feature = "py32f072",
 ))]
 inst! {
     pac::USART1;
 }
 
-#[cfg(any(feature = "py32f003", feature = "py32f030",))]
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072",))]
 inst! {
     pac::USART2;
 }
 
+#[cfg(feature = "py32f072")]
+inst! {
+    pac::USART3;
+    pac::USART4;
+}
+
 /// Serial error
 #[derive(Debug)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -273,6 +439,24 @@ pub enum Error {
     Noise,
     /// A different error occurred. The original error may contain more information.
     Other,
+    /// Auto baud rate detection failed, the reference character was not recognized or the baud
+    /// rate is out of range.
+    AutoBaudRate,
+    /// A DMA channel stopped on a bus error.
+    Dma,
+}
+
+/// Serial configuration error
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum ConfigError {
+    /// The baud rate is out of the range of the divider at the USART clock
+    ImpossibleBaudRate,
+    /// The error of the achieved baud rate exceeds the tolerance of the [Config]
+    BaudRateError(BaudCalc),
+    /// The clock output is out of the range of the prescaler at the USART clock
+    ImpossibleClock,
 }
 
 /// Length of word for Serial device
@@ -306,6 +490,33 @@ pub enum StopBits {
     STOP2,
 }
 
+/// Hardware flow control
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowControl {
+    /// No flow control
+    None,
+    /// RTS is asserted while the receiver can accept data
+    Rts,
+    /// The transmitter waits for CTS before each byte
+    Cts,
+    /// Both RTS and CTS
+    RtsCts,
+}
+
+/// Reference character measured by [Rx::auto_baud]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AutoBaudMode {
+    /// Measures the start bit, the character must begin with a 1 bit
+    StartBit,
+    /// Measures from the start bit to the next falling edge, the character must begin with
+    /// `10xx` like `0x7F` or `0x55`
+    FallingEdge,
+    /// Requires the character `0x7F`
+    Char7F,
+    /// Requires the character `0x55`
+    Char55,
+}
+
 /// Configuration for serial device
 pub struct Config {
     /// baudrate
@@ -316,6 +527,10 @@ pub struct Config {
     pub parity: Parity,
     /// number of stopbits
     pub stopbits: StopBits,
+    /// hardware flow control
+    pub flow_control: FlowControl,
+    /// tolerated baud rate error in parts per million
+    pub baud_tolerance: u32,
 }
 
 impl Config {
@@ -364,6 +579,16 @@ impl Config {
         self.stopbits = stopbits;
         self
     }
+    /// set the hardware flow control
+    pub fn flow_control(mut self, flow_control: FlowControl) -> Self {
+        self.flow_control = flow_control;
+        self
+    }
+    /// set the tolerated baud rate error in parts per million
+    pub fn baud_tolerance(mut self, ppm: u32) -> Self {
+        self.baud_tolerance = ppm;
+        self
+    }
 }
 
 impl Default for Config {
@@ -373,6 +598,8 @@ impl Default for Config {
             wordlength: WordLength::Bits8,
             parity: Parity::ParityNone,
             stopbits: StopBits::STOP1,
+            flow_control: FlowControl::None,
+            baud_tolerance: 20_000,
         }
     }
 }
@@ -417,8 +644,8 @@ impl<USART: Instance, TXPIN> Serial<USART, TXPIN, ()> {
         tx_pin: TXPIN,
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Tx<USART> {
-        Self::_new(usart, (tx_pin, ()), config, clocks).split().0
+    ) -> Result<Tx<USART>, ConfigError> {
+        Ok(Self::_new(usart, (tx_pin, ()), config, clocks)?.split().0)
     }
 }
 
@@ -429,8 +656,32 @@ impl<USART: Instance, RXPIN> Serial<USART, (), RXPIN> {
         rx_pin: RXPIN,
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Rx<USART> {
-        Self::_new(usart, ((), rx_pin), config, clocks).split().1
+    ) -> Result<Rx<USART>, ConfigError> {
+        Ok(Self::_new(usart, ((), rx_pin), config, clocks)?.split().1)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, RTSPIN, CTSPIN>
+    Serial<USART, (TXPIN, RTSPIN), (RXPIN, CTSPIN)>
+{
+    /// Configures the serial interface with hardware flow control on the `flow` pins, RTS and
+    /// CTS
+    ///
+    /// The flow control of `config` is replaced by [FlowControl::RtsCts]. [Serial::release]
+    /// returns the RTS pin with the TX pin and the CTS pin with the RX pin.
+    pub fn with_flow_control(
+        usart: USART,
+        pins: (TXPIN, RXPIN),
+        flow: (RTSPIN, CTSPIN),
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Self, ConfigError>
+    where
+        RTSPIN: RtsPin<USART>,
+        CTSPIN: CtsPin<USART>,
+    {
+        let config = config.into().flow_control(FlowControl::RtsCts);
+        Self::_new(usart, ((pins.0, flow.0), (pins.1, flow.1)), config, clocks)
     }
 }
 
@@ -446,12 +697,15 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
     ///
     /// The `serial` struct takes ownership over the `USARTX` device
     /// registers and the specified `PINS`
+    ///
+    /// Returns an error if the baud rate cannot be reached within the tolerance of the
+    /// configuration.
     pub fn new(
         usart: USART,
         pins: (TXPIN, RXPIN),
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Self {
+    ) -> Result<Self, ConfigError> {
         Self::_new(usart, (pins.0, pins.1), config, clocks)
     }
 
@@ -460,13 +714,16 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
         pins: (TXPIN, RXPIN),
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> Self {
+    ) -> Result<Self, ConfigError> {
+        let config = config.into();
+        let baud = baud_calc::<USART>(&config, clocks)?;
+
         // Enable and reset USART
         let rcc = unsafe { &(*RCC::ptr()) };
         USART::enable(rcc);
         USART::reset(rcc);
 
-        apply_config::<USART>(config.into(), clocks);
+        apply_config::<USART>(config, baud);
 
         let pins = (pins.0, pins.1);
 
@@ -479,7 +736,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
             w.re().enabled()
         });
 
-        Serial {
+        Ok(Serial {
             tx: Tx {
                 _usart: PhantomData,
             },
@@ -487,7 +744,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
                 _usart: PhantomData,
             },
             token: ReleaseToken { usart, pins },
-        }
+        })
     }
 }
 
@@ -500,7 +757,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
         &mut self,
         config: impl Into<Config>,
         clocks: &Clocks,
-    ) -> nb::Result<(), Error> {
+    ) -> nb::Result<(), ConfigError> {
         reconfigure(&mut self.tx, &mut self.rx, config, clocks)
     }
 
@@ -511,7 +768,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
     /// Basic usage:
     ///
     /// ```
-    /// let mut serial = Serial::new(usart, (tx_pin, rx_pin), 9600.bps(), &clocks);
+    /// let mut serial = Serial::new(usart, (tx_pin, rx_pin), 9600.bps(), &clocks).unwrap();
     ///
     /// // You can split the `Serial`
     /// let Serial { tx, rx, token } = serial;
@@ -541,13 +798,21 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
     }
 }
 
-fn apply_config<USART: Instance>(config: Config, clocks: &Clocks) {
+fn baud_calc<USART: Instance>(config: &Config, clocks: &Clocks) -> Result<BaudCalc, ConfigError> {
+    let baud = BaudCalc::new(USART::clock(clocks).raw(), config.baudrate.0)
+        .ok_or(ConfigError::ImpossibleBaudRate)?;
+    if baud.is_within(config.baud_tolerance) {
+        Ok(baud)
+    } else {
+        Err(ConfigError::BaudRateError(baud))
+    }
+}
+
+fn apply_config<USART: Instance>(config: Config, baud: BaudCalc) {
     let usart = unsafe { &*USART::ptr() };
 
     // Configure baud rate
-    let brr = USART::clock(clocks).raw() / config.baudrate.0;
-    assert!(brr >= 16, "impossible baud rate");
-    usart.brr.write(|w| unsafe { w.bits(brr) });
+    usart.brr.write(|w| unsafe { w.bits(baud.brr()) });
 
     // Configure word
     usart.cr1.modify(|_r, w| {
@@ -572,6 +837,18 @@ fn apply_config<USART: Instance>(config: Config, clocks: &Clocks) {
         StopBits::STOP2 => STOP_A::Stop2,
     };
     usart.cr2.modify(|_r, w| w.stop().variant(stop_bits));
+
+    // Configure flow control
+    usart.cr3.modify(|_r, w| {
+        w.rtse().bit(matches!(
+            config.flow_control,
+            FlowControl::Rts | FlowControl::RtsCts
+        ));
+        w.ctse().bit(matches!(
+            config.flow_control,
+            FlowControl::Cts | FlowControl::RtsCts
+        ))
+    });
 }
 
 /// Reconfigure the USART instance.
@@ -583,13 +860,15 @@ pub fn reconfigure<USART: Instance>(
     #[allow(unused_variables)] rx: &mut Rx<USART>,
     config: impl Into<Config>,
     clocks: &Clocks,
-) -> nb::Result<(), Error> {
+) -> nb::Result<(), ConfigError> {
+    let config = config.into();
+    let baud = baud_calc::<USART>(&config, clocks)?;
     // if we're currently busy transmitting, we have to wait until that is
     // over -- regarding reception, we assume that the caller -- with
     // exclusive access to the Serial instance due to &mut self -- knows
     // what they're doing.
-    tx.flush()?;
-    apply_config::<USART>(config.into(), clocks);
+    tx.flush().map_err(|_| nb::Error::WouldBlock)?;
+    apply_config::<USART>(config, baud);
     Ok(())
 }
 
@@ -661,6 +940,38 @@ impl<USART: Instance> Tx<USART> {
     pub fn is_tx_complete(&self) -> bool {
         unsafe { (*USART::ptr()).sr.read().tc().bit_is_set() }
     }
+
+    /// Start listening for the CTS change interrupt
+    pub fn listen_cts(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        // CR3 is also modified by the DMA and error interrupt paths
+        cortex_m::interrupt::free(|_| usart.cr3.modify(|_, w| w.ctsie().set_bit()));
+    }
+
+    /// Stop listening for the CTS change interrupt
+    pub fn unlisten_cts(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr3.modify(|_, w| w.ctsie().clear_bit()));
+    }
+
+    /// Returns true if the CTS input changed
+    pub fn is_cts_changed(&self) -> bool {
+        unsafe { (*USART::ptr()).sr.read().cts().bit_is_set() }
+    }
+
+    /// Clear CTS change interrupt flag
+    pub fn clear_cts_interrupt(&self) {
+        // writing 1 leaves the other flags untouched
+        unsafe {
+            (*USART::ptr()).sr.write(|w| {
+                w.rxne().set_bit();
+                w.tc().set_bit();
+                #[cfg(feature = "py32f072")]
+                w.lbd().set_bit();
+                w.cts().clear_bit()
+            })
+        };
+    }
 }
 
 impl<USART: Instance> core::fmt::Write for Tx<USART> {
@@ -715,6 +1026,88 @@ impl<USART: Instance> Rx<USART> {
         self.read_u16().map(|word16| word16 as u8)
     }
 
+    /// Detects the baud rate of the peer from a reference character, non-blocking
+    ///
+    /// The first call starts the detection and returns [`nb::Error::WouldBlock`] until the
+    /// reference character described by `mode` was received. The detected baud rate is then
+    /// written to the USART and to `config`, so a later `reconfigure` with `config` keeps it. The
+    /// reference character itself is discarded.
+    ///
+    /// ```rust
+    /// let mut config = Config::default();
+    /// let baud = nb::block!(rx.auto_baud(AutoBaudMode::Char7F, &mut config, &clocks))?;
+    /// ```
+    pub fn auto_baud(
+        &mut self,
+        mode: AutoBaudMode,
+        config: &mut Config,
+        clocks: &Clocks,
+    ) -> nb::Result<Bps, Error> {
+        let usart = unsafe { &*USART::ptr() };
+        if usart.cr3.read().abren().bit_is_clear() {
+            let bits = match mode {
+                AutoBaudMode::StartBit => 0b00,
+                AutoBaudMode::FallingEdge => 0b01,
+                AutoBaudMode::Char7F => 0b10,
+                AutoBaudMode::Char55 => 0b11,
+            };
+            // ABRMOD may only change while the USART is disabled
+            cortex_m::interrupt::free(|_| {
+                usart.cr1.modify(|_, w| w.ue().disabled());
+                usart.cr3.modify(|_, w| {
+                    #[cfg(not(feature = "py32f072"))]
+                    unsafe {
+                        w.abrmod().bits(bits);
+                    }
+                    #[cfg(feature = "py32f072")]
+                    unsafe {
+                        w.abrmode().bits(bits);
+                    }
+                    w.abren().set_bit()
+                });
+                usart.cr1.modify(|_, w| w.ue().enabled());
+            });
+            // clears ABRF and ABRE and starts a measurement
+            self.request_auto_baud();
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let sr = usart.sr.read();
+        if sr.abre().bit_is_set() {
+            usart.cr3.modify(|_, w| w.abren().clear_bit());
+            self.request_auto_baud();
+            return Err(nb::Error::Other(Error::AutoBaudRate));
+        }
+        if sr.abrf().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        usart.cr3.modify(|_, w| w.abren().clear_bit());
+        if sr.rxne().bit_is_set() {
+            let _ = usart.dr().read();
+        }
+        let brr = usart.brr.read().bits();
+        let baudrate = (USART::clock(clocks).raw() / brr).bps();
+        config.baudrate = baudrate;
+        Ok(baudrate)
+    }
+
+    /// Sets ABRRQ, which clears ABRF and ABRE
+    fn request_auto_baud(&self) {
+        // a read-modify-write would clear the flags that read as 0, writing 1 leaves them
+        // untouched
+        unsafe {
+            (*USART::ptr()).sr.write(|w| {
+                w.rxne().set_bit();
+                w.tc().set_bit();
+                w.cts().set_bit();
+                #[cfg(feature = "py32f072")]
+                w.lbd().set_bit();
+                w.abrrq().set_bit()
+            })
+        };
+    }
+
     /// Start listening for receive interrupt event
     pub fn listen(&mut self) {
         unsafe { (*USART::ptr()).cr1.modify(|_, w| w.rxneie().set_bit()) };
@@ -762,6 +1155,8 @@ pub enum Event {
     Rxne,
     /// Idle line state detected
     Idle,
+    /// CTS input changed
+    Cts,
 }
 
 impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
@@ -773,6 +1168,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
             Event::Rxne => self.rx.listen(),
             Event::Txe => self.tx.listen(),
             Event::Idle => self.rx.listen_idle(),
+            Event::Cts => self.tx.listen_cts(),
         }
     }
 
@@ -784,6 +1180,7 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
             Event::Rxne => self.rx.unlisten(),
             Event::Txe => self.tx.unlisten(),
             Event::Idle => self.rx.unlisten_idle(),
+            Event::Cts => self.tx.unlisten_cts(),
         }
     }
 
@@ -806,6 +1203,16 @@ impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
     pub fn clear_idle_interrupt(&self) {
         self.rx.clear_idle_interrupt();
     }
+
+    /// Returns true if the CTS input changed
+    pub fn is_cts_changed(&self) -> bool {
+        self.tx.is_cts_changed()
+    }
+
+    /// Clear CTS change interrupt flag
+    pub fn clear_cts_interrupt(&self) {
+        self.tx.clear_cts_interrupt();
+    }
 }
 
 impl<USART: Instance, TXPIN, RXPIN> core::fmt::Write for Serial<USART, TXPIN, RXPIN> {
@@ -819,11 +1226,23 @@ pub type Rx1 = Rx<pac::USART1>;
 /// type alias for [Tx] on `USART1`
 pub type Tx1 = Tx<pac::USART1>;
 /// type alias for [Rx] on `USART2`
-#[cfg(any(feature = "py32f003", feature = "py32f030",))]
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072",))]
 pub type Rx2 = Rx<pac::USART2>;
 /// type alias for [Tx] on `USART2`
-#[cfg(any(feature = "py32f003", feature = "py32f030",))]
+#[cfg(any(feature = "py32f003", feature = "py32f030", feature = "py32f072",))]
 pub type Tx2 = Tx<pac::USART2>;
+/// type alias for [Rx] on `USART3`
+#[cfg(feature = "py32f072")]
+pub type Rx3 = Rx<pac::USART3>;
+/// type alias for [Tx] on `USART3`
+#[cfg(feature = "py32f072")]
+pub type Tx3 = Tx<pac::USART3>;
+/// type alias for [Rx] on `USART4`
+#[cfg(feature = "py32f072")]
+pub type Rx4 = Rx<pac::USART4>;
+/// type alias for [Tx] on `USART4`
+#[cfg(feature = "py32f072")]
+pub type Tx4 = Tx<pac::USART4>;
 
 #[cfg(feature = "with-dma")]
 macro_rules! serialdmarx {
@@ -0,0 +1,486 @@
+//! Interrupt driven serial port with ring buffers
+//!
+//! [BufferedSerial] is the application side and [BufferedInterrupt] the interrupt side of the
+//! port. They share two [RingBuffer]s without locking: the interrupt only fills the receive
+//! buffer and drains the transmit buffer, the application does the opposite.
+//!
+//! [BufferedSerial] also implements `embedded_io_async::Read`, woken by
+//! [BufferedInterrupt::on_interrupt]. An async read returns when the line goes idle after the
+//! received bytes, or when they fill the read buffer or the ring. The bytes stay in the ring until
+//! a read returns them, so dropping a pending read loses nothing.
+//!
+//! ```rust
+//! static IRQ: Mutex<RefCell<Option<BufferedInterrupt<pac::USART1>>>> =
+//!     Mutex::new(RefCell::new(None));
+//!
+//! let rx_buffer = cortex_m::singleton!(: RingBuffer<64> = RingBuffer::new()).unwrap();
+//! let tx_buffer = cortex_m::singleton!(: RingBuffer<64> = RingBuffer::new()).unwrap();
+//! let (mut serial, irq) = BufferedSerial::new(serial, rx_buffer, tx_buffer);
+//! cortex_m::interrupt::free(|cs| *IRQ.borrow(cs).borrow_mut() = Some(irq));
+//! unsafe { NVIC::unmask(Interrupt::USART1) };
+//!
+//! #[interrupt]
+//! fn USART1() {
+//!     cortex_m::interrupt::free(|cs| {
+//!         if let Some(irq) = IRQ.borrow(cs).borrow_mut().as_mut() {
+//!             irq.on_interrupt();
+//!         }
+//!     });
+//! }
+//! ```
+
+use super::{Error, Instance, Serial};
+
+use core::cell::UnsafeCell;
+use core::future::poll_fn;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use core::task::Poll;
+
+/// Byte ring buffer shared by a [BufferedSerial] and its [BufferedInterrupt]
+///
+/// One slot is kept free to tell a full buffer from an empty one, so the buffer holds up to
+/// `N - 1` bytes.
+pub struct RingBuffer<const N: usize> {
+    data: UnsafeCell<[u8; N]>,
+    read: AtomicUsize,
+    write: AtomicUsize,
+    /// Receive error latched by the interrupt, 0 if none
+    error: AtomicU8,
+    /// Set by the interrupt when the line goes idle after a received byte
+    idle: AtomicBool,
+    /// Number of bytes a pending async read waits for, 0 if none
+    wanted: AtomicUsize,
+}
+
+// NOTE(unsafe) a slot is only accessed by the producer before it is published by `write`, and by
+// the consumer after that, until it is released by `read`
+unsafe impl<const N: usize> Sync for RingBuffer<N> {}
+
+impl<const N: usize> RingBuffer<N> {
+    /// Creates an empty buffer
+    pub const fn new() -> Self {
+        assert!(N > 1, "a ring buffer needs at least 2 bytes");
+        Self {
+            data: UnsafeCell::new([0; N]),
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+            error: AtomicU8::new(0),
+            idle: AtomicBool::new(false),
+            wanted: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<const N: usize> Default for RingBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Size erased view of a [RingBuffer]
+#[derive(Clone, Copy)]
+struct Ring {
+    data: *mut u8,
+    len: usize,
+    read: &'static AtomicUsize,
+    write: &'static AtomicUsize,
+    error: &'static AtomicU8,
+    idle: &'static AtomicBool,
+    wanted: &'static AtomicUsize,
+}
+
+// NOTE(unsafe) `data` points into a `'static` [RingBuffer]
+unsafe impl Send for Ring {}
+
+impl Ring {
+    fn new<const N: usize>(buffer: &'static RingBuffer<N>) -> Self {
+        Self {
+            data: buffer.data.get() as *mut u8,
+            len: N,
+            read: &buffer.read,
+            write: &buffer.write,
+            error: &buffer.error,
+            idle: &buffer.idle,
+            wanted: &buffer.wanted,
+        }
+    }
+
+    fn next(&self, index: usize) -> usize {
+        if index + 1 == self.len {
+            0
+        } else {
+            index + 1
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.read.load(Ordering::Acquire) == self.write.load(Ordering::Acquire)
+    }
+
+    fn is_full(&self) -> bool {
+        self.next(self.write.load(Ordering::Acquire)) == self.read.load(Ordering::Acquire)
+    }
+
+    /// Number of bytes in the buffer
+    fn available(&self) -> usize {
+        let read = self.read.load(Ordering::Acquire);
+        let write = self.write.load(Ordering::Acquire);
+        (write + self.len - read) % self.len
+    }
+
+    /// Producer side: appends a byte, returns false if the buffer is full
+    fn push(&self, byte: u8) -> bool {
+        let write = self.write.load(Ordering::Relaxed);
+        let next = self.next(write);
+        if next == self.read.load(Ordering::Acquire) {
+            return false;
+        }
+        unsafe { self.data.add(write).write_volatile(byte) };
+        self.write.store(next, Ordering::Release);
+        true
+    }
+
+    /// Consumer side: returns the oldest byte without removing it
+    fn peek(&self) -> Option<u8> {
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.write.load(Ordering::Acquire) {
+            None
+        } else {
+            Some(unsafe { self.data.add(read).read_volatile() })
+        }
+    }
+
+    /// Consumer side: removes the byte returned by [Ring::peek]
+    fn consume(&self) {
+        let read = self.read.load(Ordering::Relaxed);
+        self.read.store(self.next(read), Ordering::Release);
+    }
+
+    /// Interrupt side: latches `error` unless an earlier one is not reported yet
+    fn latch_error(&self, error: Error) {
+        if self.error.load(Ordering::Relaxed) == 0 {
+            self.error.store(error.code(), Ordering::Relaxed);
+        }
+    }
+
+    /// Application side: takes the latched error
+    fn take_error(&self) -> Option<Error> {
+        cortex_m::interrupt::free(|_| {
+            let code = self.error.load(Ordering::Relaxed);
+            self.error.store(0, Ordering::Relaxed);
+            Error::from_code(code)
+        })
+    }
+}
+
+impl Error {
+    fn code(&self) -> u8 {
+        match self {
+            Error::Overrun => 1,
+            Error::FrameFormat => 2,
+            Error::Parity => 3,
+            Error::Noise => 4,
+            Error::Other => 5,
+            Error::AutoBaudRate => 6,
+            Error::Dma => 7,
+        }
+    }
+
+    fn from_code(code: u8) -> Option<Self> {
+        match code {
+            0 => None,
+            1 => Some(Error::Overrun),
+            2 => Some(Error::FrameFormat),
+            3 => Some(Error::Parity),
+            4 => Some(Error::Noise),
+            6 => Some(Error::AutoBaudRate),
+            7 => Some(Error::Dma),
+            _ => Some(Error::Other),
+        }
+    }
+}
+
+/// Serial port transferring data through ring buffers from the USART interrupt
+///
+/// Receive errors are latched and returned by the next read that finds the receive buffer empty,
+/// so the bytes received before the error are read first. A byte received while the buffer is
+/// full is dropped and reported as [Error::Overrun].
+pub struct BufferedSerial<USART: Instance, TXPIN, RXPIN> {
+    serial: Serial<USART, TXPIN, RXPIN>,
+    rx: Ring,
+    tx: Ring,
+}
+
+/// Interrupt side of a [BufferedSerial]
+pub struct BufferedInterrupt<USART> {
+    rx: Ring,
+    tx: Ring,
+    _usart: PhantomData<USART>,
+}
+
+impl<USART: Instance, TXPIN, RXPIN> BufferedSerial<USART, TXPIN, RXPIN> {
+    /// Takes over `serial` with the given buffers and enables the receive interrupts
+    ///
+    /// The returned [BufferedInterrupt] has to be serviced from the USART interrupt handler.
+    pub fn new<const RX: usize, const TX: usize>(
+        serial: Serial<USART, TXPIN, RXPIN>,
+        rx_buffer: &'static mut RingBuffer<RX>,
+        tx_buffer: &'static mut RingBuffer<TX>,
+    ) -> (Self, BufferedInterrupt<USART>) {
+        let rx = Ring::new(rx_buffer);
+        let tx = Ring::new(tx_buffer);
+        let usart = unsafe { &*USART::ptr() };
+        // drop what was received before the buffers existed
+        let _ = usart.sr.read();
+        let _ = usart.dr().read();
+        usart
+            .cr1
+            .modify(|_, w| w.rxneie().set_bit().idleie().set_bit().peie().set_bit());
+        (
+            Self { serial, rx, tx },
+            BufferedInterrupt {
+                rx,
+                tx,
+                _usart: PhantomData,
+            },
+        )
+    }
+
+    /// Disables the interrupts and returns the serial port
+    ///
+    /// Data still in the buffers is discarded.
+    pub fn release(self) -> Serial<USART, TXPIN, RXPIN> {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| {
+            usart.cr1.modify(|_, w| {
+                w.rxneie().clear_bit();
+                w.idleie().clear_bit();
+                w.peie().clear_bit();
+                w.txeie().clear_bit()
+            })
+        });
+        self.serial
+    }
+
+    /// Returns true if a write blocks until the interrupt makes room in the transmit buffer
+    pub fn is_tx_full(&self) -> bool {
+        self.tx.is_full()
+    }
+
+    /// Returns true if a read returns without blocking
+    pub fn is_rx_ready(&self) -> bool {
+        !self.rx.is_empty() || self.rx.error.load(Ordering::Relaxed) != 0
+    }
+
+    fn start_tx(&self) {
+        let usart = unsafe { &*USART::ptr() };
+        // the interrupt clears TXEIE when the buffer runs empty
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.txeie().set_bit()));
+    }
+
+    /// Moves the buffered bytes into `buffer`, returns the number moved
+    fn take(&mut self, buffer: &mut [u8]) -> usize {
+        let mut count = 0;
+        while count < buffer.len() {
+            match self.rx.peek() {
+                Some(byte) => {
+                    buffer[count] = byte;
+                    self.rx.consume();
+                    count += 1;
+                }
+                None => break,
+            }
+        }
+        // the idle line ended the bytes taken, a later idle line sets it again
+        cortex_m::interrupt::free(|_| {
+            if self.rx.is_empty() {
+                self.rx.idle.store(false, Ordering::Release);
+            }
+        });
+        count
+    }
+
+    /// Reads the buffered bytes into `buffer`, blocking until there is at least one
+    pub fn read(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let count = self.take(buffer);
+            if count > 0 {
+                return Ok(count);
+            }
+            if let Some(error) = self.rx.take_error() {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Reads the buffered bytes into `buffer` once the line goes idle after them, or once they
+    /// fill `buffer` or the ring
+    async fn read_async(&mut self, buffer: &mut [u8]) -> Result<usize, Error> {
+        if buffer.is_empty() {
+            return Ok(0);
+        }
+        let wanted = buffer.len().min(self.rx.len - 1);
+        poll_fn(|cx| {
+            let available = self.rx.available();
+            // bytes received before an error are returned first
+            let ended =
+                self.rx.idle.load(Ordering::Acquire) || self.rx.error.load(Ordering::Relaxed) != 0;
+            if available >= wanted || (available > 0 && ended) {
+                self.rx.wanted.store(0, Ordering::Relaxed);
+                return Poll::Ready(Ok(self.take(buffer)));
+            }
+            if let Some(error) = self.rx.take_error() {
+                self.rx.wanted.store(0, Ordering::Relaxed);
+                return Poll::Ready(Err(error));
+            }
+            USART::state().rx.register(cx.waker());
+            self.rx.wanted.store(wanted, Ordering::Release);
+            // the interrupt may have received the bytes before the waker was registered
+            let now = self.rx.available();
+            if now != available || (now > 0 && self.rx.idle.load(Ordering::Acquire)) {
+                cx.waker().wake_by_ref();
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Queues bytes for transmission, blocking until at least one fits into the buffer
+    pub fn write(&mut self, bytes: &[u8]) -> Result<usize, Error> {
+        if bytes.is_empty() {
+            return Ok(0);
+        }
+        loop {
+            let mut count = 0;
+            while count < bytes.len() && self.tx.push(bytes[count]) {
+                count += 1;
+            }
+            if count > 0 {
+                self.start_tx();
+                return Ok(count);
+            }
+        }
+    }
+
+    /// Waits until the transmit buffer is empty and the last byte has left the shift register
+    pub fn flush(&mut self) -> Result<(), Error> {
+        while !self.tx.is_empty() {}
+        self.serial.tx.bflush()
+    }
+}
+
+impl<USART: Instance> BufferedInterrupt<USART> {
+    /// Moves data between the USART and the buffers
+    ///
+    /// Call from the USART interrupt handler.
+    pub fn on_interrupt(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        let sr = usart.sr.read();
+
+        let error = if sr.pe().bit_is_set() {
+            Some(Error::Parity)
+        } else if sr.fe().bit_is_set() {
+            Some(Error::FrameFormat)
+        } else if sr.ne().bit_is_set() {
+            Some(Error::Noise)
+        } else if sr.ore().bit_is_set() {
+            Some(Error::Overrun)
+        } else {
+            None
+        };
+        let mut wake = false;
+        if sr.rxne().bit_is_set() || error.is_some() || sr.idle().bit_is_set() {
+            // reading DR after SR clears RXNE, IDLE and the error flags
+            let byte = usart.dr().read().dr().bits() as u8;
+            // a byte with a parity or framing error is corrupted, the others are kept
+            let valid = !matches!(error, Some(Error::Parity | Error::FrameFormat));
+            if sr.rxne().bit_is_set() && valid {
+                if self.rx.push(byte) {
+                    self.rx.idle.store(false, Ordering::Release);
+                } else {
+                    self.rx.latch_error(Error::Overrun);
+                }
+            }
+            if let Some(error) = error {
+                self.rx.latch_error(error);
+                wake = true;
+            }
+            if sr.idle().bit_is_set() && !self.rx.is_empty() {
+                self.rx.idle.store(true, Ordering::Release);
+                wake = true;
+            }
+            let wanted = self.rx.wanted.load(Ordering::Acquire);
+            if wake || (wanted != 0 && self.rx.available() >= wanted) {
+                USART::state().rx.wake();
+            }
+        }
+
+        if sr.txe().bit_is_set() && usart.cr1.read().txeie().bit_is_set() {
+            match self.tx.peek() {
+                Some(byte) => {
+                    usart.dr().write(|w| w.dr().bits(byte as u16));
+                    // release the slot after DR is written, so `flush` cannot miss the byte
+                    self.tx.consume();
+                }
+                None => usart.cr1.modify(|_, w| w.txeie().clear_bit()),
+            }
+        }
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> core::fmt::Write for BufferedSerial<USART, TXPIN, RXPIN> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        let mut bytes = s.as_bytes();
+        while !bytes.is_empty() {
+            let count = self.write(bytes).map_err(|_| core::fmt::Error)?;
+            bytes = &bytes[count..];
+        }
+        Ok(())
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::ErrorType for BufferedSerial<USART, TXPIN, RXPIN> {
+    type Error = Error;
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::Read for BufferedSerial<USART, TXPIN, RXPIN> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        BufferedSerial::read(self, buf)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io_async::Read
+    for BufferedSerial<USART, TXPIN, RXPIN>
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.read_async(buf).await
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::ReadReady for BufferedSerial<USART, TXPIN, RXPIN> {
+    fn read_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(self.is_rx_ready())
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::Write for BufferedSerial<USART, TXPIN, RXPIN> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        BufferedSerial::write(self, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        BufferedSerial::flush(self)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::WriteReady
+    for BufferedSerial<USART, TXPIN, RXPIN>
+{
+    fn write_ready(&mut self) -> Result<bool, Self::Error> {
+        Ok(!self.tx.is_full())
+    }
+}
@@ -0,0 +1,377 @@
+//! Reception of variable length frames with circular DMA
+//!
+//! [FrameReader] keeps the receive DMA channel running in circular mode over a static buffer.
+//! [FrameReader::read_frame] returns the bytes received since the previous call when the line
+//! went idle, which ends a frame, or when the DMA passed the middle or the end of the buffer. The
+//! bytes are borrowed from the buffer in up to two slices, the second one when the data wraps
+//! around the end of the buffer.
+//!
+//! The DMA keeps writing while a [Frame] is borrowed, so the buffer must be large enough to hold
+//! the bytes received until the frame is processed. It should be at least twice the longest
+//! frame.
+//!
+//! The reader counts the bytes received from the DMA position and the half and full transfer
+//! flags. If the DMA overwrites bytes not read yet, the next read fails with [Error::Overrun] and
+//! reception resumes with the following bytes. A lap is only told apart from a shorter advance
+//! if the reader is updated at least every half buffer, which the interrupts enabled by
+//! [FrameReader::listen] or by a pending async read ensure.
+//!
+//! [FrameReader] also implements `embedded_io_async::Read`, returning when the line goes idle or
+//! the buffer is half full. The bytes stay in the buffer until a read returns them, so dropping a
+//! pending read loses nothing. The waiting read enables the USART idle line and error interrupts
+//! and the DMA half and full transfer interrupts, so [super::on_interrupt] and
+//! [crate::dma::on_interrupt] must be bound to them, and [FrameReader::listen] cannot be used
+//! meanwhile.
+//!
+//! ```rust
+//! let buffer = cortex_m::singleton!(: [u8; 256] = [0; 256]).unwrap();
+//! let mut reader = rx.with_dma(dma.2).frame_reader(buffer);
+//! loop {
+//!     if let Ok(Some(frame)) = reader.read_frame() {
+//!         let (first, second) = frame.slices();
+//!         // ...
+//!     }
+//! }
+//! ```
+
+use super::{Error, Instance, Rx};
+use crate::dma::{Ch, DmaExt, Event, RxDma};
+
+use core::future::poll_fn;
+use core::sync::atomic::{compiler_fence, Ordering};
+use core::task::Poll;
+
+/// Bytes received since the previous [FrameReader::read_frame]
+pub struct Frame<'a> {
+    first: &'a [u8],
+    second: &'a [u8],
+    complete: bool,
+}
+
+impl<'a> Frame<'a> {
+    /// The bytes in order, the second slice is not empty if they wrap around the buffer end
+    pub fn slices(&self) -> (&'a [u8], &'a [u8]) {
+        (self.first, self.second)
+    }
+
+    /// Number of bytes
+    pub fn len(&self) -> usize {
+        self.first.len() + self.second.len()
+    }
+
+    /// Returns true if no bytes were received since the previous frame
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns true if the line went idle after the bytes, ending the frame
+    ///
+    /// Otherwise the bytes are the first part of a frame still being received.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Iterates over the bytes
+    pub fn iter(&self) -> impl Iterator<Item = u8> + 'a {
+        self.first.iter().chain(self.second).copied()
+    }
+
+    /// Copies the bytes into `buffer`, returns the number of bytes copied
+    pub fn copy_to(&self, buffer: &mut [u8]) -> usize {
+        let first = self.first.len().min(buffer.len());
+        buffer[..first].copy_from_slice(&self.first[..first]);
+        let second = self.second.len().min(buffer.len() - first);
+        buffer[first..first + second].copy_from_slice(&self.second[..second]);
+        first + second
+    }
+}
+
+/// Receiver of variable length frames, see the [module](self) documentation
+pub struct FrameReader<USART: Instance, DMA: DmaExt, const C: u8> {
+    rx: RxDma<Rx<USART>, Ch<DMA, C>>,
+    buffer: &'static mut [u8],
+    read: usize,
+    /// Index of the next byte written by the DMA, at the previous update
+    write: usize,
+    /// Number of bytes received and not read yet
+    pending: usize,
+    /// The line went idle after the bytes still in the buffer
+    idle: bool,
+    /// The pending error flags have been reported
+    error_reported: bool,
+}
+
+impl<USART: Instance, DMA: DmaExt, const C: u8> RxDma<Rx<USART>, Ch<DMA, C>> {
+    /// Starts circular reception into `buffer` and returns a [FrameReader] over it
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of `buffer` is zero, odd or above 65535 bytes.
+    pub fn frame_reader(mut self, buffer: &'static mut [u8]) -> FrameReader<USART, DMA, C> {
+        // with an even length, the half transfer flag is set exactly at the middle
+        assert!(
+            !buffer.is_empty() && buffer.len() % 2 == 0 && buffer.len() <= u16::MAX as usize,
+            "invalid frame buffer length"
+        );
+        let usart = unsafe { &*USART::ptr() };
+        self.channel
+            .set_peripheral_address(usart.dr().as_ptr() as u32, false);
+        self.channel
+            .set_memory_address(buffer.as_mut_ptr() as u32, true);
+        self.channel.set_transfer_length(buffer.len());
+
+        compiler_fence(Ordering::Release);
+
+        self.channel.ch().cr.modify(|_, w| {
+            w.mem2mem().clear_bit();
+            w.pl().medium();
+            w.msize().bits8();
+            w.psize().bits8();
+            w.circ().set_bit();
+            w.dir().clear_bit()
+        });
+        // drop a stale idle flag
+        let _ = usart.sr.read();
+        let _ = usart.dr().read();
+        self.channel.start();
+
+        FrameReader {
+            rx: self,
+            buffer,
+            read: 0,
+            write: 0,
+            pending: 0,
+            idle: false,
+            error_reported: false,
+        }
+    }
+}
+
+impl<USART: Instance, DMA: DmaExt, const C: u8> FrameReader<USART, DMA, C> {
+    /// Returns the bytes received since the previous call, if the line went idle or the DMA
+    /// passed the middle or the end of the buffer since then
+    ///
+    /// Receive errors are returned first, the bytes are then returned by the next call. If the DMA
+    /// overwrote bytes not read yet, fails with [Error::Overrun] and drops the bytes received so
+    /// far.
+    pub fn read_frame(&mut self) -> Result<Option<Frame<'_>>, Error> {
+        if let Some(err) = self.take_error() {
+            return Err(err);
+        }
+        let idle = self.take_idle();
+        let passed = self.update()?;
+        if !(idle || passed) {
+            return Ok(None);
+        }
+
+        let len = self.buffer.len();
+        let read = self.read;
+        let end = read + self.pending;
+        self.read = self.write;
+        self.pending = 0;
+        self.idle = false;
+        compiler_fence(Ordering::Acquire);
+
+        let (first, second) = if end <= len {
+            (&self.buffer[read..end], &self.buffer[..0])
+        } else {
+            (&self.buffer[read..], &self.buffer[..end - len])
+        };
+        Ok(Some(Frame {
+            first,
+            second,
+            complete: idle,
+        }))
+    }
+
+    /// Returns the pending receive error, reporting it once
+    ///
+    /// Only SR is read: reading DR could take a byte before the DMA does. The error flags are
+    /// cleared when the DMA reads DR for the next byte, until then they are not reported again.
+    fn take_error(&mut self) -> Option<Error> {
+        let sr = unsafe { (*USART::ptr()).sr.read() };
+        let err = if sr.pe().bit_is_set() {
+            Some(Error::Parity)
+        } else if sr.fe().bit_is_set() {
+            Some(Error::FrameFormat)
+        } else if sr.ne().bit_is_set() {
+            Some(Error::Noise)
+        } else if sr.ore().bit_is_set() {
+            Some(Error::Overrun)
+        } else {
+            None
+        };
+        let reported = self.error_reported;
+        self.error_reported = err.is_some();
+        err.filter(|_| !reported)
+    }
+
+    /// Returns true if the line went idle, clearing the flag
+    ///
+    /// DR is only read once the DMA is done with the last byte: the DMA position must not move
+    /// around the SR read, and DR is read right after it with interrupts disabled.
+    fn take_idle(&mut self) -> bool {
+        let usart = unsafe { &*USART::ptr() };
+        let channel = &self.rx.channel;
+        cortex_m::interrupt::free(|_| {
+            let ndtr = channel.get_ndtr();
+            let sr = usart.sr.read();
+            if sr.idle().bit_is_set() && sr.rxne().bit_is_clear() && channel.get_ndtr() == ndtr {
+                // the DMA took the last byte, reading DR after SR only clears the flags
+                let _ = usart.dr().read();
+            }
+            // otherwise the DMA reads DR for the last byte, which clears IDLE
+            sr.idle().bit_is_set()
+        })
+    }
+
+    /// Index of the next byte the DMA writes
+    fn write_index(&self) -> usize {
+        let len = self.buffer.len();
+        (len - self.rx.channel.get_ndtr() as usize) % len
+    }
+
+    /// Counts the bytes received since the previous update, returns true if the DMA passed the
+    /// middle or the end of the buffer
+    ///
+    /// Fails with [Error::Overrun] if the DMA passed a boundary more often than the advance of its
+    /// index tells, or wrote more bytes than the buffer holds, and then drops the pending bytes.
+    fn update(&mut self) -> Result<bool, Error> {
+        let len = self.buffer.len();
+        let mid = len / 2;
+        // the flags are read between two indexes in the same half, so they match the index
+        let (write, half, full) = loop {
+            let before = self.write_index();
+            let isr = self.rx.channel.isr();
+            let half = unsafe { isr.htif(C).bit_is_set() };
+            let full = unsafe { isr.tcif(C).bit_is_set() };
+            let after = self.write_index();
+            if after >= before && (before < mid) == (after < mid) {
+                break (after, half, full);
+            }
+        };
+        // only the flags read are cleared, one set meanwhile is counted by the next update
+        let flags = (u32::from(half) << 2) | (u32::from(full) << 1);
+        if flags != 0 {
+            // TODO: do bit ops to get chtif and ctcif flags cleared, until pac has indexing method
+            self.rx
+                .channel
+                .ifcr()
+                .write(|w| unsafe { w.bits(flags << ((C - 1) * 4)) });
+        }
+
+        let start = self.write;
+        let advance = (write + len - start) % len;
+        let passed_mid = (start < mid && start + advance >= mid) || start + advance >= mid + len;
+        let passed_end = start + advance >= len;
+        self.write = write;
+        self.pending += advance;
+        if (half && !passed_mid) || (full && !passed_end) || self.pending > len {
+            self.read = write;
+            self.pending = 0;
+            self.idle = false;
+            return Err(Error::Overrun);
+        }
+        Ok(half || full)
+    }
+
+    /// Moves up to `buf.len()` of the pending bytes into `buf`, returns the number moved
+    fn copy_out(&mut self, buf: &mut [u8]) -> usize {
+        compiler_fence(Ordering::Acquire);
+        let len = self.buffer.len();
+        let count = self.pending.min(buf.len());
+        let first = count.min(len - self.read);
+        buf[..first].copy_from_slice(&self.buffer[self.read..self.read + first]);
+        buf[first..count].copy_from_slice(&self.buffer[..count - first]);
+        self.read = (self.read + count) % len;
+        self.pending -= count;
+        if self.pending == 0 {
+            self.idle = false;
+        }
+        count
+    }
+
+    /// Enables the USART idle line and the DMA half and full transfer interrupts
+    ///
+    /// The handlers call [FrameReader::read_frame], which clears the flags.
+    pub fn listen(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.idleie().set_bit()));
+        self.rx.channel.listen(Event::HalfTransfer);
+        self.rx.channel.listen(Event::TransferComplete);
+    }
+
+    /// Disables the interrupts enabled by [FrameReader::listen]
+    pub fn unlisten(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.idleie().clear_bit()));
+        self.rx.channel.unlisten(Event::HalfTransfer);
+        self.rx.channel.unlisten(Event::TransferComplete);
+    }
+
+    /// Stops the reception and returns the receiver and the buffer
+    pub fn release(mut self) -> (RxDma<Rx<USART>, Ch<DMA, C>>, &'static mut [u8]) {
+        self.unlisten();
+        // left enabled by a dropped async read
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| {
+            usart.cr1.modify(|_, w| w.peie().clear_bit());
+            usart.cr3.modify(|_, w| w.eie().clear_bit());
+        });
+        self.rx.channel.unlisten_async();
+        self.rx.channel.stop();
+        compiler_fence(Ordering::SeqCst);
+        (self.rx, self.buffer)
+    }
+}
+
+impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io::ErrorType
+    for FrameReader<USART, DMA, C>
+{
+    type Error = Error;
+}
+
+impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io_async::Read
+    for FrameReader<USART, DMA, C>
+{
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            if let Some(err) = self.take_error() {
+                return Poll::Ready(Err(err));
+            }
+            let idle = self.take_idle();
+            if let Err(err) = self.update() {
+                return Poll::Ready(Err(err));
+            }
+            let pending = self.pending;
+            // an idle line after the bytes already returned ends no frame
+            if idle && pending > 0 {
+                self.idle = true;
+            }
+            let ready = self.idle || pending >= buf.len() || pending >= self.buffer.len() / 2;
+            if pending > 0 && ready {
+                return Poll::Ready(Ok(self.copy_out(buf)));
+            }
+
+            USART::state().rx.register(cx.waker());
+            self.rx.channel.waker().register(cx.waker());
+            self.rx.channel.listen_async(Event::HalfTransfer);
+            self.rx.channel.listen_async(Event::TransferComplete);
+            // the error flags stay set until the next byte, listening to them meanwhile would
+            // interrupt again at once
+            let errors = !self.error_reported;
+            let usart = unsafe { &*USART::ptr() };
+            cortex_m::interrupt::free(|_| {
+                usart
+                    .cr1
+                    .modify(|_, w| w.idleie().set_bit().peie().bit(errors));
+                usart.cr3.modify(|_, w| w.eie().bit(errors));
+            });
+            Poll::Pending
+        })
+        .await
+    }
+}
@@ -12,7 +12,7 @@ mod nb {
                 Error::FrameFormat => ErrorKind::FrameFormat,
                 Error::Parity => ErrorKind::Parity,
                 Error::Noise => ErrorKind::Noise,
-                Error::Other => ErrorKind::Other,
+                Error::Other | Error::AutoBaudRate | Error::Dma => ErrorKind::Other,
             }
         }
     }
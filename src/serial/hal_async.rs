@@ -0,0 +1,420 @@
+//! Async reads and writes completing from the USART and DMA interrupts
+//!
+//! A waiting future enables the interrupt it needs, and [on_interrupt] disables it again and
+//! wakes the task. The flags are left for the future to inspect. The application binds
+//! [on_interrupt] to the USART interrupt, and [crate::dma::on_interrupt] to the DMA interrupts
+//! when DMA is used:
+//!
+//! ```rust
+//! #[interrupt]
+//! fn USART1() {
+//!     py32f0xx_hal::serial::on_interrupt::<pac::USART1>();
+//! }
+//! ```
+//!
+//! Reads of [Rx] return the bytes available as soon as there is one, and take nothing from the
+//! data register while they wait, so dropping a pending read loses no data. Without a buffer
+//! they cannot wait for the line to go idle. Reads returning when the line goes idle are provided
+//! by [super::BufferedSerial], filled from the USART interrupt, and by [super::FrameReader], filled
+//! by circular DMA. Both keep the received bytes in their ring buffer until a read returns them,
+//! so dropping a pending read loses no data either.
+//!
+//! DMA reads complete when the buffer is full, or when the line goes idle after at least one byte.
+//! DMA writes complete when the last byte is handed to the USART. Both fail with [Error::Dma] if
+//! the channel stops on a bus error. Dropping a pending DMA read or write stops the channel and
+//! disables its interrupts, so the next transfer starts from a clean state, but the bytes already
+//! transferred are not reported.
+//!
+//! The async transfers use the same interrupt enables as [super::BufferedSerial] and the
+//! `listen` methods, so they cannot be mixed on one USART.
+
+use super::{Error, Instance, Rx, Tx};
+use crate::waker::WakerSlot;
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+/// Wakers of the tasks waiting on a USART
+#[doc(hidden)]
+pub struct State {
+    rx: WakerSlot,
+    tx: WakerSlot,
+}
+
+impl State {
+    pub(crate) const fn new() -> Self {
+        Self {
+            rx: WakerSlot::new(),
+            tx: WakerSlot::new(),
+        }
+    }
+}
+
+/// USART interrupt handler for async transfers
+///
+/// Disables the enabled receive or transmit interrupts that have a pending event, and wakes the
+/// waiting task.
+pub fn on_interrupt<USART: Instance>() {
+    // NOTE(unsafe) only interrupt enable bits are modified, and the tasks waiting on them are woken
+    let usart = unsafe { &*USART::ptr() };
+    let sr = usart.sr.read();
+    let cr1 = usart.cr1.read();
+    let cr3 = usart.cr3.read();
+
+    let errors = sr.fe().bit_is_set() || sr.ne().bit_is_set() || sr.ore().bit_is_set();
+    let rx = (cr1.rxneie().bit_is_set() && (sr.rxne().bit_is_set() || sr.ore().bit_is_set()))
+        || (cr1.idleie().bit_is_set() && sr.idle().bit_is_set())
+        || (cr1.peie().bit_is_set() && sr.pe().bit_is_set())
+        || (cr3.eie().bit_is_set() && errors);
+    if rx {
+        usart.cr1.modify(|_, w| {
+            w.rxneie().clear_bit();
+            w.idleie().clear_bit();
+            w.peie().clear_bit()
+        });
+        usart.cr3.modify(|_, w| w.eie().clear_bit());
+        USART::state().rx.wake();
+    }
+
+    let tx = (cr1.txeie().bit_is_set() && sr.txe().bit_is_set())
+        || (cr1.tcie().bit_is_set() && sr.tc().bit_is_set());
+    if tx {
+        usart
+            .cr1
+            .modify(|_, w| w.txeie().clear_bit().tcie().clear_bit());
+        USART::state().tx.wake();
+    }
+}
+
+impl<USART: Instance> Rx<USART> {
+    async fn read_async(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            let mut count = 0;
+            while count < buf.len() {
+                match self.read() {
+                    Ok(byte) => {
+                        buf[count] = byte;
+                        count += 1;
+                    }
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(e)) => return Poll::Ready(Err(e)),
+                }
+            }
+            if count > 0 {
+                return Poll::Ready(Ok(count));
+            }
+            USART::state().rx.register(cx.waker());
+            // framing, noise and overrun errors come with RXNE, parity errors need PEIE
+            cortex_m::interrupt::free(|_| {
+                unsafe { &*USART::ptr() }
+                    .cr1
+                    .modify(|_, w| w.rxneie().set_bit().peie().set_bit())
+            });
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<USART: Instance> Tx<USART> {
+    async fn write_async(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        poll_fn(|cx| {
+            let mut count = 0;
+            while count < buf.len() {
+                match self.write_u8(buf[count]) {
+                    Ok(()) => count += 1,
+                    Err(nb::Error::WouldBlock) => break,
+                    Err(nb::Error::Other(e)) => return Poll::Ready(Err(e)),
+                }
+            }
+            if count > 0 {
+                return Poll::Ready(Ok(count));
+            }
+            USART::state().tx.register(cx.waker());
+            cortex_m::interrupt::free(|_| {
+                unsafe { &*USART::ptr() }
+                    .cr1
+                    .modify(|_, w| w.txeie().set_bit())
+            });
+            Poll::Pending
+        })
+        .await
+    }
+
+    async fn flush_async(&mut self) -> Result<(), Error> {
+        poll_fn(|cx| {
+            if self.is_tx_complete() {
+                return Poll::Ready(Ok(()));
+            }
+            USART::state().tx.register(cx.waker());
+            cortex_m::interrupt::free(|_| {
+                unsafe { &*USART::ptr() }
+                    .cr1
+                    .modify(|_, w| w.tcie().set_bit())
+            });
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<USART: Instance> embedded_io_async::Read for Rx<USART> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.read_async(buf).await
+    }
+}
+
+impl<USART: Instance> embedded_io_async::Write for Tx<USART> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.write_async(buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.flush_async().await
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io_async::Read for super::Serial<USART, TXPIN, RXPIN> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.rx.read_async(buf).await
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io_async::Write
+    for super::Serial<USART, TXPIN, RXPIN>
+{
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.tx.write_async(buf).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        self.tx.flush_async().await
+    }
+}
+
+#[cfg(feature = "with-dma")]
+mod dma {
+    use super::{Error, Instance, Rx, Tx};
+    use crate::dma::{Ch, DmaExt, Event, RxDma, TxDma};
+
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::sync::atomic::{compiler_fence, Ordering};
+    use core::task::{Context, Poll};
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io::ErrorType
+        for TxDma<Tx<USART>, Ch<DMA, C>>
+    {
+        type Error = Error;
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io::ErrorType
+        for RxDma<Rx<USART>, Ch<DMA, C>>
+    {
+        type Error = Error;
+    }
+
+    /// Sets up a one shot byte transfer between `ptr` and the data register
+    fn start<USART: Instance, DMA: DmaExt, const C: u8>(
+        channel: &mut Ch<DMA, C>,
+        ptr: u32,
+        len: usize,
+        from_memory: bool,
+    ) {
+        channel.set_peripheral_address(unsafe { (*USART::ptr()).dr().as_ptr() as u32 }, false);
+        channel.set_memory_address(ptr, true);
+        channel.set_transfer_length(len);
+
+        compiler_fence(Ordering::Release);
+
+        channel.ch().cr.modify(|_, w| {
+            w.mem2mem().clear_bit();
+            w.pl().medium();
+            w.msize().bits8();
+            w.psize().bits8();
+            w.circ().clear_bit();
+            w.dir().bit(from_memory)
+        });
+        channel.start();
+    }
+
+    /// DMA read, completing when `len` bytes are received, or when the line goes idle after the
+    /// first one
+    struct ReadFuture<'a, USART: Instance, DMA: DmaExt, const C: u8> {
+        rx: &'a mut Rx<USART>,
+        channel: &'a mut Ch<DMA, C>,
+        buf: *mut u8,
+        len: usize,
+        running: bool,
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> ReadFuture<'_, USART, DMA, C> {
+        fn finish(&mut self) {
+            cortex_m::interrupt::free(|_| {
+                let usart = unsafe { &*USART::ptr() };
+                usart
+                    .cr1
+                    .modify(|_, w| w.idleie().clear_bit().peie().clear_bit());
+                usart.cr3.modify(|_, w| w.eie().clear_bit());
+            });
+            self.channel.unlisten_async();
+            self.channel.stop();
+            self.running = false;
+            compiler_fence(Ordering::Acquire);
+        }
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> Future for ReadFuture<'_, USART, DMA, C> {
+        type Output = Result<usize, Error>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let usart = unsafe { &*USART::ptr() };
+            if self.channel.transfer_error() {
+                self.finish();
+                return Poll::Ready(Err(Error::Dma));
+            }
+            if !self.channel.in_progress() {
+                self.finish();
+                return Poll::Ready(Ok(self.len));
+            }
+            let sr = usart.sr.read();
+            let errors = sr.pe().bit_is_set()
+                || sr.fe().bit_is_set()
+                || sr.ne().bit_is_set()
+                || sr.ore().bit_is_set();
+            if errors {
+                self.finish();
+                // with the channel stopped, reading DR reports and clears the error
+                return match self.rx.read() {
+                    Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+                    _ => Poll::Ready(Err(Error::Other)),
+                };
+            }
+            let mut received = self.len - self.channel.get_ndtr() as usize;
+            if sr.idle().bit_is_set() {
+                if received > 0 {
+                    self.finish();
+                    // a byte the DMA did not take before it stopped is still in DR
+                    if usart.sr.read().rxne().bit_is_set() {
+                        let byte = usart.dr().read().dr().bits() as u8;
+                        unsafe { self.buf.add(received).write_volatile(byte) };
+                        received += 1;
+                    } else {
+                        // reading DR after SR clears IDLE
+                        let _ = usart.dr().read();
+                    }
+                    return Poll::Ready(Ok(received));
+                }
+                // left from before the read, the DMA has not started to receive
+                if sr.rxne().bit_is_clear() {
+                    let _ = usart.dr().read();
+                }
+            }
+            self.channel.waker().register(cx.waker());
+            USART::state().rx.register(cx.waker());
+            self.channel.listen_async(Event::TransferComplete);
+            self.channel.listen_async_error();
+            // framing, noise and overrun errors need EIE with DMA, parity errors need PEIE
+            cortex_m::interrupt::free(|_| {
+                usart
+                    .cr1
+                    .modify(|_, w| w.idleie().set_bit().peie().set_bit());
+                usart.cr3.modify(|_, w| w.eie().set_bit());
+            });
+            Poll::Pending
+        }
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> Drop for ReadFuture<'_, USART, DMA, C> {
+        fn drop(&mut self) {
+            if self.running {
+                self.finish();
+            }
+        }
+    }
+
+    /// DMA write, completing when the last byte is handed to the USART
+    struct WriteFuture<'a, DMA: DmaExt, const C: u8> {
+        channel: &'a mut Ch<DMA, C>,
+        running: bool,
+    }
+
+    impl<DMA: DmaExt, const C: u8> Future for WriteFuture<'_, DMA, C> {
+        type Output = Result<(), Error>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let result = if self.channel.transfer_error() {
+                Err(Error::Dma)
+            } else if !self.channel.in_progress() {
+                Ok(())
+            } else {
+                self.channel.waker().register(cx.waker());
+                self.channel.listen_async(Event::TransferComplete);
+                self.channel.listen_async_error();
+                return Poll::Pending;
+            };
+            self.channel.unlisten_async();
+            self.channel.stop();
+            self.running = false;
+            compiler_fence(Ordering::Acquire);
+            Poll::Ready(result)
+        }
+    }
+
+    impl<DMA: DmaExt, const C: u8> Drop for WriteFuture<'_, DMA, C> {
+        fn drop(&mut self) {
+            if self.running {
+                self.channel.unlisten_async();
+                self.channel.stop();
+            }
+        }
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io_async::Write
+        for TxDma<Tx<USART>, Ch<DMA, C>>
+    {
+        async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            let len = buf.len().min(u16::MAX as usize);
+            start::<USART, DMA, C>(&mut self.channel, buf.as_ptr() as u32, len, true);
+            WriteFuture {
+                channel: &mut self.channel,
+                running: true,
+            }
+            .await?;
+            Ok(len)
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            self.payload.flush_async().await
+        }
+    }
+
+    impl<USART: Instance, DMA: DmaExt, const C: u8> embedded_io_async::Read
+        for RxDma<Rx<USART>, Ch<DMA, C>>
+    {
+        async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+            if buf.is_empty() {
+                return Ok(0);
+            }
+            let len = buf.len().min(u16::MAX as usize);
+            start::<USART, DMA, C>(&mut self.channel, buf.as_mut_ptr() as u32, len, false);
+            ReadFuture {
+                rx: &mut self.payload,
+                channel: &mut self.channel,
+                buf: buf.as_mut_ptr(),
+                len,
+                running: true,
+            }
+            .await
+        }
+    }
+}
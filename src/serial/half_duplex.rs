@@ -0,0 +1,130 @@
+//! Single wire half-duplex mode
+//!
+//! With `HDSEL` set the USART transmits and receives on its TX pin, which is driven open-drain
+//! and needs a pull-up. [HalfDuplex] switches the receiver off while it transmits, so its own
+//! bytes are not read back.
+//!
+//! ```rust
+//! let mut pin = gpioa.pa2.into_alternate_af1();
+//! pin.set_internal_pull(Pull::Up);
+//! let mut bus = p.USART1.half_duplex(pin, 1_000_000.bps(), &rcc.clocks)?;
+//! bus.bwrite_all(&[0xff, 0xff, 0x01, 0x02, 0x01, 0xfb])?;
+//! let status = nb::block!(bus.read())?;
+//! ```
+
+use super::{Config, ConfigError, Error, Instance, Serial, TxPin};
+use crate::gpio::{Alternate, Pin};
+use crate::rcc::Clocks;
+
+/// Serial port transmitting and receiving on one open-drain pin
+pub struct HalfDuplex<USART: Instance, PIN> {
+    serial: Serial<USART, PIN, ()>,
+}
+
+impl<USART: Instance, const P: char, const N: u8, AF> Serial<USART, Pin<P, N, Alternate<AF>>, ()> {
+    /// Configures the USART for single wire half-duplex communication on its TX `pin`
+    ///
+    /// The pin is switched to open-drain.
+    pub fn half_duplex(
+        usart: USART,
+        mut pin: Pin<P, N, Alternate<AF>>,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<HalfDuplex<USART, Pin<P, N, Alternate<AF>>>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<USART>,
+    {
+        pin.set_open_drain(true);
+        let serial = Self::_new(usart, (pin, ()), config, clocks)?;
+        let usart = unsafe { &*USART::ptr() };
+        // HDSEL may only change while the USART is disabled
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr3.modify(|_, w| w.hdsel().set_bit());
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        Ok(HalfDuplex { serial })
+    }
+}
+
+impl<USART: Instance, PIN> HalfDuplex<USART, PIN> {
+    /// Transmits `bytes`, blocking until the last one has left the line
+    ///
+    /// The receiver is off meanwhile, so the bytes are not read back.
+    pub fn bwrite_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.re().disabled());
+        let result = self
+            .serial
+            .tx
+            .bwrite_all_u8(bytes)
+            .and_then(|_| self.serial.tx.bflush());
+        usart.cr1.modify(|_, w| w.re().enabled());
+        result
+    }
+
+    /// Reads a received byte, non-blocking
+    pub fn read(&mut self) -> nb::Result<u8, Error> {
+        self.serial.rx.read()
+    }
+
+    /// Reconfigures the USART, for example to change the baud rate between the reset and data
+    /// phases of a 1-Wire bus
+    ///
+    /// If a transmission is currently in progress, this returns [`nb::Error::WouldBlock`].
+    pub fn reconfigure(
+        &mut self,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> nb::Result<(), ConfigError> {
+        self.serial.reconfigure(config, clocks)
+    }
+
+    /// Leaves half-duplex mode and returns the USART and the pin
+    pub fn release(self) -> (USART, PIN) {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr3.modify(|_, w| w.hdsel().clear_bit());
+        let (usart, (pin, ())) = self.serial.release();
+        (usart, pin)
+    }
+}
+
+impl<USART: Instance, PIN> core::fmt::Write for HalfDuplex<USART, PIN> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.bwrite_all(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl<USART: Instance, PIN> embedded_io::ErrorType for HalfDuplex<USART, PIN> {
+    type Error = Error;
+}
+
+impl<USART: Instance, PIN> embedded_io::Read for HalfDuplex<USART, PIN> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = nb::block!(HalfDuplex::read(self))?;
+        let mut count = 1;
+        while count < buf.len() {
+            match HalfDuplex::read(self) {
+                Ok(byte) => buf[count] = byte,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<USART: Instance, PIN> embedded_io::Write for HalfDuplex<USART, PIN> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.bwrite_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // writes return once the line is released
+        Ok(())
+    }
+}
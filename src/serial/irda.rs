@@ -0,0 +1,152 @@
+//! IrDA SIR mode
+//!
+//! With `IREN` set the USART encodes each 0 bit as a short pulse on TX and decodes the pulses
+//! received on RX, for an infrared transceiver or an optical head. IrDA is half-duplex: [Irda]
+//! switches the receiver off while it transmits, so the light of its own LED is not read back.
+//! IrDA SIR runs at up to 115200 bps with one stop bit.
+//!
+//! In normal mode the pulses are 3/16 of a bit. The py32f072 also has a low-power mode, with
+//! pulses of 3 periods of the USART clock divided by a prescaler.
+//!
+//! ```rust
+//! let mut head = p.USART1.irda((tx, rx), IrdaMode::Normal, 9600.bps(), &clocks)?;
+//! head.bwrite_all(b"/?!\r\n")?;
+//! let byte = nb::block!(head.read())?;
+//! ```
+
+use super::{Config, ConfigError, Error, Instance, RxPin, Serial, TxPin};
+use crate::rcc::Clocks;
+
+/// IrDA pulse mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IrdaMode {
+    /// Pulses of 3/16 of a bit
+    Normal,
+    /// Pulses of 3 periods of the USART clock divided by the prescaler, which must not be 0
+    #[cfg(feature = "py32f072")]
+    LowPower(u8),
+}
+
+/// Serial port in IrDA SIR mode, see the [module](self) documentation
+pub struct Irda<USART: Instance, TXPIN, RXPIN> {
+    serial: Serial<USART, TXPIN, RXPIN>,
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
+    /// Configures the USART for IrDA SIR, transmitting on `pins.0` and receiving on `pins.1`
+    pub fn irda(
+        usart: USART,
+        pins: (TXPIN, RXPIN),
+        mode: IrdaMode,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> Result<Irda<USART, TXPIN, RXPIN>, ConfigError>
+    where
+        TXPIN: TxPin<USART>,
+        RXPIN: RxPin<USART>,
+    {
+        let serial = Self::_new(usart, pins, config, clocks)?;
+        let usart = unsafe { &*USART::ptr() };
+        // IREN may only change while the USART is disabled
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        #[cfg(feature = "py32f072")]
+        {
+            let psc = match mode {
+                IrdaMode::Normal => 1,
+                IrdaMode::LowPower(psc) => psc,
+            };
+            usart.gtpr.modify(|_, w| unsafe { w.psc().bits(psc) });
+        }
+        usart.cr3.modify(|_, w| {
+            w.irlp().bit(mode != IrdaMode::Normal);
+            w.iren().set_bit()
+        });
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        Ok(Irda { serial })
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Irda<USART, TXPIN, RXPIN> {
+    /// Transmits `bytes`, blocking until the last one has left the line
+    ///
+    /// The receiver is off meanwhile, so the bytes are not read back.
+    pub fn bwrite_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.re().disabled());
+        let result = self
+            .serial
+            .tx
+            .bwrite_all_u8(bytes)
+            .and_then(|_| self.serial.tx.bflush());
+        usart.cr1.modify(|_, w| w.re().enabled());
+        result
+    }
+
+    /// Reads a received byte, non-blocking
+    pub fn read(&mut self) -> nb::Result<u8, Error> {
+        self.serial.rx.read()
+    }
+
+    /// Reconfigures the USART, keeping the IrDA mode
+    ///
+    /// If a transmission is currently in progress, this returns [`nb::Error::WouldBlock`].
+    pub fn reconfigure(
+        &mut self,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> nb::Result<(), ConfigError> {
+        self.serial.reconfigure(config, clocks)
+    }
+
+    /// Leaves IrDA mode and returns the USART and the pins
+    pub fn release(self) -> (USART, (TXPIN, RXPIN)) {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr3.modify(|_, w| {
+            w.irlp().clear_bit();
+            w.iren().clear_bit()
+        });
+        self.serial.release()
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> core::fmt::Write for Irda<USART, TXPIN, RXPIN> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.bwrite_all(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::ErrorType for Irda<USART, TXPIN, RXPIN> {
+    type Error = Error;
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::Read for Irda<USART, TXPIN, RXPIN> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = nb::block!(Irda::read(self))?;
+        let mut count = 1;
+        while count < buf.len() {
+            match Irda::read(self) {
+                Ok(byte) => buf[count] = byte,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> embedded_io::Write for Irda<USART, TXPIN, RXPIN> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.bwrite_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // writes return once the last byte is sent
+        Ok(())
+    }
+}
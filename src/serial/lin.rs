@@ -0,0 +1,207 @@
+//! LIN mode
+//!
+//! With `LINEN` set the USART sends 13 bit break fields and detects breaks on the line. [Lin]
+//! builds headers and responses on top of it with the encoding of the [`lin`](crate::lin)
+//! module. LIN mode is only available on the py32f072.
+//!
+//! A LIN transceiver returns everything on the bus to RX, so every byte written is read back and
+//! compared, which also reports collisions. The blocking calls wait until the bytes arrive.
+//!
+//! ```rust
+//! let mut lin = p.USART1.serial((tx, rx), 19_200.bps(), &clocks)?.lin(BreakLength::Bits11);
+//! // master: request frame 0x10 from a slave
+//! lin.write_header(0x10)?;
+//! let mut data = [0; 4];
+//! lin.read_response(0x10, &mut data, ChecksumKind::Enhanced)?;
+//! ```
+
+use super::{Error, Instance, Serial};
+use crate::lin::{self, ChecksumKind};
+
+/// Length of the break detected by the receiver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakLength {
+    /// 10 bit break detection
+    Bits10,
+    /// 11 bit break detection
+    Bits11,
+}
+
+/// LIN error
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum LinError {
+    /// The USART reported an error
+    Serial(Error),
+    /// A byte read back differs from the byte written
+    BitError,
+    /// The byte after the break is not the sync byte
+    Sync,
+    /// The parity bits of the protected identifier are wrong
+    Parity,
+    /// The frame identifier is larger than [`lin::MAX_ID`] or the data is longer than
+    /// [`lin::MAX_DATA_LEN`]
+    InvalidFrame,
+    /// The checksum of the response is wrong
+    Checksum,
+}
+
+impl From<Error> for LinError {
+    fn from(err: Error) -> Self {
+        LinError::Serial(err)
+    }
+}
+
+/// Serial port in LIN mode, see the [module](self) documentation
+pub struct Lin<USART: Instance, TXPIN, RXPIN> {
+    serial: Serial<USART, TXPIN, RXPIN>,
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
+    /// Switches the USART to LIN mode
+    ///
+    /// The USART should be configured for 8 data bits, no parity and one stop bit.
+    pub fn lin(self, break_length: BreakLength) -> Lin<USART, TXPIN, RXPIN> {
+        let usart = unsafe { &*USART::ptr() };
+        // LINEN may only change while the USART is disabled
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr2.modify(|_, w| {
+            w.clken().clear_bit();
+            w.lbdl().bit(break_length == BreakLength::Bits11);
+            w.linen().set_bit()
+        });
+        usart.cr3.modify(|_, w| {
+            w.scen().clear_bit();
+            w.hdsel().clear_bit();
+            w.iren().clear_bit()
+        });
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        Lin { serial: self }
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Lin<USART, TXPIN, RXPIN> {
+    /// Sends a break field after the current byte
+    pub fn send_break(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.sbk().set_bit()));
+    }
+
+    /// Returns true if a break was detected
+    pub fn is_break_detected(&self) -> bool {
+        unsafe { (*USART::ptr()).sr.read().lbd().bit_is_set() }
+    }
+
+    /// Clears the break detection flag and drops the zero byte received with the break
+    pub fn clear_break(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        // writing 1 leaves the other flags untouched
+        usart.sr.write(|w| {
+            w.rxne().set_bit();
+            w.tc().set_bit();
+            w.cts().set_bit();
+            w.lbd().clear_bit()
+        });
+        // the break is also received as a zero byte with a framing error
+        let _ = usart.sr.read();
+        let _ = usart.dr().read();
+    }
+
+    /// Start listening for the break detection interrupt
+    pub fn listen_break(&mut self) {
+        unsafe { (*USART::ptr()).cr2.modify(|_, w| w.lbdie().set_bit()) };
+    }
+
+    /// Stop listening for the break detection interrupt
+    pub fn unlisten_break(&mut self) {
+        unsafe { (*USART::ptr()).cr2.modify(|_, w| w.lbdie().clear_bit()) };
+    }
+
+    /// Sends the header of frame `id`: a break, the sync byte and the protected identifier
+    pub fn write_header(&mut self, id: u8) -> Result<(), LinError> {
+        let pid = lin::protected_id(id).ok_or(LinError::InvalidFrame)?;
+        nb::block!(self.serial.tx.flush())?;
+        self.send_break();
+        while !self.is_break_detected() {}
+        self.clear_break();
+        self.write_checked(&[lin::SYNC, pid])
+    }
+
+    /// Sends the `data` of frame `id` followed by its checksum
+    pub fn write_response(
+        &mut self,
+        id: u8,
+        data: &[u8],
+        checksum: ChecksumKind,
+    ) -> Result<(), LinError> {
+        let pid = lin::protected_id(id).ok_or(LinError::InvalidFrame)?;
+        if data.len() > lin::MAX_DATA_LEN {
+            return Err(LinError::InvalidFrame);
+        }
+        self.write_checked(data)?;
+        self.write_checked(&[lin::checksum(checksum, pid, data)])
+    }
+
+    /// Waits for a header and returns its frame identifier
+    ///
+    /// Bytes before the break are dropped.
+    pub fn read_header(&mut self) -> Result<u8, LinError> {
+        while !self.is_break_detected() {
+            // drop the bytes of the previous frame
+            let _ = self.serial.rx.read();
+        }
+        self.clear_break();
+        if nb::block!(self.serial.rx.read())? != lin::SYNC {
+            return Err(LinError::Sync);
+        }
+        let pid = nb::block!(self.serial.rx.read())?;
+        lin::frame_id(pid).ok_or(LinError::Parity)
+    }
+
+    /// Reads the response of frame `id` into `data` and checks its checksum
+    ///
+    /// The length of the response is the length of `data`.
+    pub fn read_response(
+        &mut self,
+        id: u8,
+        data: &mut [u8],
+        checksum: ChecksumKind,
+    ) -> Result<(), LinError> {
+        let pid = lin::protected_id(id).ok_or(LinError::InvalidFrame)?;
+        if data.len() > lin::MAX_DATA_LEN {
+            return Err(LinError::InvalidFrame);
+        }
+        for byte in data.iter_mut() {
+            *byte = nb::block!(self.serial.rx.read())?;
+        }
+        let received = nb::block!(self.serial.rx.read())?;
+        if lin::verify_checksum(checksum, pid, data, received) {
+            Ok(())
+        } else {
+            Err(LinError::Checksum)
+        }
+    }
+
+    /// Leaves LIN mode and returns the serial port
+    pub fn release(self) -> Serial<USART, TXPIN, RXPIN> {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr2.modify(|_, w| {
+            w.lbdie().clear_bit();
+            w.linen().clear_bit()
+        });
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        self.serial
+    }
+
+    fn write_checked(&mut self, bytes: &[u8]) -> Result<(), LinError> {
+        for &byte in bytes {
+            nb::block!(self.serial.tx.write_u8(byte))?;
+            if nb::block!(self.serial.rx.read())? != byte {
+                return Err(LinError::BitError);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,125 @@
+//! Multiprocessor communication
+//!
+//! Several nodes share one serial line. A receiver in mute mode ignores the traffic, without
+//! setting flags or raising interrupts, until it is woken:
+//!
+//! - by an idle line, after which it receives until it is muted again
+//! - by an address mark carrying its node address. A word is an address mark when its most
+//!   significant bit is set, with 9 bit words this is bit 8, which keeps the 8 data bits free.
+//!   Address marks for other nodes mute the receiver again.
+//!
+//! ```rust
+//! let config = Config::default().wordlength_9bits();
+//! let (mut tx, mut rx) = p.USART1.serial((tx, rx), config, &clocks)?.split();
+//! rx.set_wake_up(WakeUp::AddressMark(0x3));
+//! rx.mute();
+//! // on another node
+//! tx.bwrite_addressed(0x3, b"hello")?;
+//! ```
+
+use super::{Error, Instance, Rx, Tx};
+
+/// Wake up method of a muted receiver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WakeUp {
+    /// Wake up on an idle line
+    IdleLine,
+    /// Wake up on an address mark with this node address, the lower 4 bits are used
+    AddressMark(u8),
+}
+
+/// Word of an addressed transfer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum AddressedWord {
+    /// Address mark, bit 8 set
+    Address(u8),
+    /// Data, bit 8 clear
+    Data(u8),
+}
+
+impl AddressedWord {
+    /// Returns the 9 bit word
+    pub const fn to_u16(self) -> u16 {
+        match self {
+            AddressedWord::Address(address) => 0x100 | address as u16,
+            AddressedWord::Data(data) => data as u16,
+        }
+    }
+
+    /// Splits a 9 bit word
+    pub const fn from_u16(word: u16) -> Self {
+        if word & 0x100 != 0 {
+            AddressedWord::Address(word as u8)
+        } else {
+            AddressedWord::Data(word as u8)
+        }
+    }
+}
+
+impl<USART: Instance> Rx<USART> {
+    /// Selects how the receiver leaves mute mode
+    ///
+    /// Leaves mute mode. The USART is disabled meanwhile, so this waits for the transmission in
+    /// progress to complete first.
+    pub fn set_wake_up(&mut self, wake_up: WakeUp) {
+        let usart = unsafe { &*USART::ptr() };
+        // clearing UE would cut the frame being sent by the Tx half
+        while usart.sr.read().tc().bit_is_clear() {}
+        cortex_m::interrupt::free(|_| {
+            // WAKE may only change while the USART is disabled
+            usart.cr1.modify(|_, w| {
+                w.rwu().clear_bit();
+                w.ue().disabled()
+            });
+            if let WakeUp::AddressMark(address) = wake_up {
+                usart
+                    .cr2
+                    .modify(|_, w| unsafe { w.add().bits(address & 0xf) });
+            }
+            usart.cr1.modify(|_, w| {
+                w.wake().bit(matches!(wake_up, WakeUp::AddressMark(_)));
+                w.ue().enabled()
+            });
+        });
+    }
+
+    /// Enters mute mode, until the wake up condition selected by [Rx::set_wake_up]
+    pub fn mute(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.rwu().set_bit()));
+    }
+
+    /// Leaves mute mode
+    pub fn unmute(&mut self) {
+        let usart = unsafe { &*USART::ptr() };
+        cortex_m::interrupt::free(|_| usart.cr1.modify(|_, w| w.rwu().clear_bit()));
+    }
+
+    /// Returns true if the receiver is in mute mode
+    pub fn is_muted(&self) -> bool {
+        unsafe { (*USART::ptr()).cr1.read().rwu().bit_is_set() }
+    }
+
+    /// Reads a 9 bit word and tells address marks from data, non-blocking
+    pub fn read_addressed(&mut self) -> nb::Result<AddressedWord, Error> {
+        self.read_u16().map(AddressedWord::from_u16)
+    }
+}
+
+impl<USART: Instance> Tx<USART> {
+    /// Writes an address mark for `address`, non-blocking
+    ///
+    /// The USART must be configured with `WordLength::Bits9`.
+    pub fn write_address(&mut self, address: u8) -> nb::Result<(), Error> {
+        self.write_u16(AddressedWord::Address(address).to_u16())
+    }
+
+    /// Writes an address mark for `address` followed by `data`, blocking
+    ///
+    /// The USART must be configured with `WordLength::Bits9`.
+    pub fn bwrite_addressed(&mut self, address: u8, data: &[u8]) -> Result<(), Error> {
+        nb::block!(self.write_address(address))?;
+        self.bwrite_all_u8(data)
+    }
+}
@@ -0,0 +1,265 @@
+//! RS-485 transceiver driver enable
+//!
+//! The USARTs of the PY32F0 have no driver enable output, so the DE (and inverted RE) input of
+//! the transceiver is driven by a GPIO. [DriverEnable] raises it before the first byte and
+//! lowers it once the USART reports transmission complete, so the last stop bit is on the bus.
+//! Optional guard times are added after raising and before lowering it.
+//!
+//! [Rs485] bundles the serial port and its [DriverEnable] for blocking use. Its fields are public,
+//! so a DMA transmission can use the driver enable directly:
+//!
+//! ```rust
+//! let Rs485 { serial, mut driver } = p.USART1.serial((tx, rx), 9600.bps(), &clocks)?
+//!     .rs485(de, delay, Rs485Config::default().deassert_time(100.micros()));
+//! let tx = serial.split().0.with_dma(dma.ch2);
+//! driver.assert();
+//! let (_, tx) = tx.write(buffer).wait();
+//! nb::block!(driver.release()).ok();
+//! ```
+
+use super::{Config, ConfigError, Error, Instance, Serial};
+use crate::rcc::Clocks;
+use crate::time::MicroSeconds;
+
+use core::marker::PhantomData;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+
+/// Guard times of the driver enable
+#[derive(Clone, Copy, Debug)]
+pub struct Rs485Config {
+    /// Time between raising DE and the start of the first byte
+    pub assert_time: MicroSeconds,
+    /// Time between the end of the last stop bit and lowering DE
+    pub deassert_time: MicroSeconds,
+}
+
+impl Rs485Config {
+    /// set the assertion guard time
+    pub fn assert_time(mut self, time: MicroSeconds) -> Self {
+        self.assert_time = time;
+        self
+    }
+    /// set the deassertion guard time
+    pub fn deassert_time(mut self, time: MicroSeconds) -> Self {
+        self.deassert_time = time;
+        self
+    }
+}
+
+impl Default for Rs485Config {
+    fn default() -> Self {
+        Self {
+            assert_time: MicroSeconds::from_ticks(0),
+            deassert_time: MicroSeconds::from_ticks(0),
+        }
+    }
+}
+
+/// Driver enable pin of an RS-485 transceiver on a USART
+pub struct DriverEnable<USART, DE, DELAY> {
+    de: DE,
+    delay: DELAY,
+    config: Rs485Config,
+    asserted: bool,
+    _usart: PhantomData<USART>,
+}
+
+impl<USART: Instance, DE: OutputPin, DELAY: DelayNs> DriverEnable<USART, DE, DELAY> {
+    fn new(mut de: DE, delay: DELAY, config: Rs485Config) -> Self {
+        de.set_low().ok();
+        Self {
+            de,
+            delay,
+            config,
+            asserted: false,
+            _usart: PhantomData,
+        }
+    }
+
+    /// Raises DE and waits for the assertion guard time, unless DE is already high
+    ///
+    /// The transmission complete flag left by a previous transmission is cleared, so
+    /// [DriverEnable::release] waits for the bytes written after this call, also when they are
+    /// handed to the USART by DMA.
+    pub fn assert(&mut self) {
+        if !self.asserted {
+            // writing 1 leaves the other flags untouched
+            unsafe {
+                (*USART::ptr()).sr.write(|w| {
+                    w.rxne().set_bit();
+                    w.cts().set_bit();
+                    #[cfg(feature = "py32f072")]
+                    w.lbd().set_bit();
+                    w.tc().clear_bit()
+                })
+            };
+            self.de.set_high().ok();
+            self.delay.delay_us(self.config.assert_time.ticks());
+            self.asserted = true;
+        }
+    }
+
+    /// Lowers DE after the deassertion guard time, once the last byte is transmitted
+    ///
+    /// Returns [`nb::Error::WouldBlock`] while the USART is still transmitting.
+    pub fn release(&mut self) -> nb::Result<(), Error> {
+        if !self.asserted {
+            return Ok(());
+        }
+        if unsafe { (*USART::ptr()).sr.read().tc().bit_is_clear() } {
+            return Err(nb::Error::WouldBlock);
+        }
+        self.delay.delay_us(self.config.deassert_time.ticks());
+        self.de.set_low().ok();
+        self.asserted = false;
+        Ok(())
+    }
+
+    /// Returns true if DE is high
+    pub fn is_asserted(&self) -> bool {
+        self.asserted
+    }
+
+    /// Start listening for the transmission complete interrupt, to call
+    /// [DriverEnable::release] from the USART interrupt handler
+    pub fn listen(&mut self) {
+        cortex_m::interrupt::free(|_| unsafe {
+            (*USART::ptr()).cr1.modify(|_, w| w.tcie().set_bit())
+        });
+    }
+
+    /// Stop listening for the transmission complete interrupt
+    pub fn unlisten(&mut self) {
+        cortex_m::interrupt::free(|_| unsafe {
+            (*USART::ptr()).cr1.modify(|_, w| w.tcie().clear_bit())
+        });
+    }
+
+    /// Releases the pin and the delay
+    pub fn free(self) -> (DE, DELAY) {
+        (self.de, self.delay)
+    }
+}
+
+/// Serial port on an RS-485 transceiver
+pub struct Rs485<USART: Instance, TXPIN, RXPIN, DE, DELAY> {
+    /// The serial port
+    pub serial: Serial<USART, TXPIN, RXPIN>,
+    /// The driver enable of the transceiver
+    pub driver: DriverEnable<USART, DE, DELAY>,
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
+    /// Drives the transceiver enable `de` around transmissions, lowering it right away
+    ///
+    /// `delay` times the guard times of `config`.
+    pub fn rs485<DE: OutputPin, DELAY: DelayNs>(
+        self,
+        de: DE,
+        delay: DELAY,
+        config: Rs485Config,
+    ) -> Rs485<USART, TXPIN, RXPIN, DE, DELAY> {
+        Rs485 {
+            serial: self,
+            driver: DriverEnable::new(de, delay, config),
+        }
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE: OutputPin, DELAY: DelayNs>
+    Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    /// Transmits `bytes`, blocking until DE is lowered again
+    pub fn bwrite_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        self.driver.assert();
+        let result = self.serial.tx.bwrite_all_u8(bytes);
+        nb::block!(self.driver.release())?;
+        result
+    }
+
+    /// Reads a received byte, non-blocking
+    pub fn read(&mut self) -> nb::Result<u8, Error> {
+        self.serial.rx.read()
+    }
+
+    /// Reconfigures the USART
+    ///
+    /// If a transmission is currently in progress, this returns [`nb::Error::WouldBlock`].
+    pub fn reconfigure(
+        &mut self,
+        config: impl Into<Config>,
+        clocks: &Clocks,
+    ) -> nb::Result<(), ConfigError> {
+        self.serial.reconfigure(config, clocks)
+    }
+
+    /// Returns the serial port, the driver enable pin and the delay
+    pub fn release(self) -> (Serial<USART, TXPIN, RXPIN>, DE, DELAY) {
+        let (de, delay) = self.driver.free();
+        (self.serial, de, delay)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE: OutputPin, DELAY: DelayNs> core::fmt::Write
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        self.bwrite_all(s.as_bytes()).map_err(|_| core::fmt::Error)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE, DELAY> embedded_io::ErrorType
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    type Error = Error;
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE: OutputPin, DELAY: DelayNs> embedded_io::Write
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    /// Raises DE and queues the bytes, DE is lowered by [embedded_io::Write::flush]
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.driver.assert();
+        embedded_io::Write::write(&mut self.serial.tx, buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        nb::block!(self.driver.release())
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE: OutputPin, DELAY: DelayNs> embedded_io::Read
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = nb::block!(self.serial.rx.read())?;
+        let mut count = 1;
+        while count < buf.len() {
+            match self.serial.rx.read() {
+                Ok(byte) => buf[count] = byte,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE, DELAY> embedded_hal_nb::serial::ErrorType
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    type Error = Error;
+}
+
+impl<USART: Instance, TXPIN, RXPIN, DE, DELAY> embedded_hal_nb::serial::Read<u8>
+    for Rs485<USART, TXPIN, RXPIN, DE, DELAY>
+{
+    fn read(&mut self) -> nb::Result<u8, Self::Error> {
+        self.serial.rx.read()
+    }
+}
@@ -0,0 +1,216 @@
+//! Smartcard mode
+//!
+//! With `SCEN` set the USART talks to an ISO 7816 card: the card I/O is the TX pin, driven
+//! open-drain and needing a pull-up, and the card clock is the CK pin, the USART clock divided by
+//! an even prescaler. Characters have 8 data bits, even parity and 1.5 stop bits, and the bit
+//! duration (etu) is a number of card clock periods, 372 after a reset. Smartcard mode is only
+//! available on the py32f072.
+//!
+//! The reset and supply of the card are GPIOs, the answer to reset is read with
+//! [Smartcard::read] once the reset line is released.
+//!
+//! ```rust
+//! let mut io = gpioa.pa9.into_alternate_af1();
+//! io.set_internal_pull(Pull::Up);
+//! let mut card = p.USART1.smartcard((io, ck), SmartcardConfig::default(), &clocks)?;
+//! rst.set_high();
+//! let ts = nb::block!(card.read())?;
+//! ```
+
+use super::{CkPin, Config, ConfigError, Error, Instance, Serial, TxPin};
+use crate::gpio::{Alternate, Pin};
+use crate::rcc::{BusClock, Clocks};
+use crate::time::{Hertz, U32Ext};
+
+/// Smartcard configuration
+#[derive(Clone, Copy, Debug)]
+pub struct SmartcardConfig {
+    /// Card clock on the CK pin, rounded to the nearest even division of the USART clock
+    pub clock: Hertz,
+    /// Card clock periods per bit, F/D of ISO 7816
+    pub clocks_per_etu: u16,
+    /// Bit durations between the stop bits of a character and transmission complete
+    pub guard_time: u8,
+    /// Reply to characters with a parity error with a NACK
+    pub nack: bool,
+}
+
+impl SmartcardConfig {
+    /// set the card clock
+    pub fn clock(mut self, clock: Hertz) -> Self {
+        self.clock = clock;
+        self
+    }
+    /// set the card clock periods per bit
+    pub fn clocks_per_etu(mut self, clocks_per_etu: u16) -> Self {
+        self.clocks_per_etu = clocks_per_etu;
+        self
+    }
+    /// set the guard time
+    pub fn guard_time(mut self, guard_time: u8) -> Self {
+        self.guard_time = guard_time;
+        self
+    }
+    /// set whether parity errors are answered with a NACK
+    pub fn nack(mut self, nack: bool) -> Self {
+        self.nack = nack;
+        self
+    }
+}
+
+impl Default for SmartcardConfig {
+    fn default() -> Self {
+        Self {
+            clock: 4.MHz(),
+            clocks_per_etu: 372,
+            guard_time: 16,
+            nack: true,
+        }
+    }
+}
+
+/// Serial port in smartcard mode, see the [module](self) documentation
+pub struct Smartcard<USART: Instance, IOPIN, CKPIN> {
+    serial: Serial<USART, IOPIN, ()>,
+    ck: CKPIN,
+}
+
+impl<USART: Instance, const P: char, const N: u8, AF> Serial<USART, Pin<P, N, Alternate<AF>>, ()> {
+    /// Configures the USART for a smartcard with its I/O on the TX pin `pins.0` and its clock on
+    /// `pins.1`
+    ///
+    /// The I/O pin is switched to open-drain. Returns an error if the card clock is out of the
+    /// range of the prescaler, or the baud rate out of the range of the divider.
+    pub fn smartcard<CKPIN>(
+        usart: USART,
+        pins: (Pin<P, N, Alternate<AF>>, CKPIN),
+        config: SmartcardConfig,
+        clocks: &Clocks,
+    ) -> Result<Smartcard<USART, Pin<P, N, Alternate<AF>>, CKPIN>, ConfigError>
+    where
+        Pin<P, N, Alternate<AF>>: TxPin<USART>,
+        CKPIN: CkPin<USART>,
+    {
+        let (mut io, ck) = pins;
+        let clock = USART::clock(clocks).raw();
+        let div = 2 * config.clock.raw().max(1);
+        let psc = (clock + div / 2) / div;
+        if psc == 0 || psc > 31 || config.clocks_per_etu == 0 {
+            return Err(ConfigError::ImpossibleClock);
+        }
+        let etu = 2 * psc * config.clocks_per_etu as u32;
+        let serial_config = Config::default()
+            .baudrate(((clock + etu / 2) / etu).bps())
+            .wordlength_9bits()
+            .parity_even();
+        io.set_open_drain(true);
+        let serial = Self::_new(usart, (io, ()), serial_config, clocks)?;
+        let usart = unsafe { &*USART::ptr() };
+        // the smartcard settings may only change while the USART is disabled
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.gtpr.write(|w| unsafe {
+            w.psc().bits(psc as u8);
+            w.gt().bits(config.guard_time)
+        });
+        usart.cr2.modify(|r, w| {
+            // 1.5 stop bits
+            unsafe { w.bits(r.bits() | (0b11 << 12)) };
+            w.cpol().clear_bit();
+            w.cpha().clear_bit();
+            w.lbcl().set_bit();
+            w.clken().set_bit()
+        });
+        usart.cr3.modify(|_, w| {
+            w.hdsel().clear_bit();
+            w.iren().clear_bit();
+            w.nack().bit(config.nack);
+            w.scen().set_bit()
+        });
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        Ok(Smartcard { serial, ck })
+    }
+}
+
+impl<USART: Instance, IOPIN, CKPIN> Smartcard<USART, IOPIN, CKPIN> {
+    /// Transmits `bytes`, blocking until the guard time of the last one has elapsed
+    ///
+    /// The receiver is off meanwhile, so the bytes are not read back. A character rejected by
+    /// the card with a NACK is reported as [`Error::FrameFormat`].
+    pub fn bwrite_all(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.re().disabled());
+        let result = self
+            .serial
+            .tx
+            .bwrite_all_u8(bytes)
+            .and_then(|_| self.serial.tx.bflush());
+        usart.cr1.modify(|_, w| w.re().enabled());
+        result?;
+        if usart.sr.read().fe().bit_is_set() {
+            // reading DR after SR clears the flag
+            let _ = usart.dr().read();
+            return Err(Error::FrameFormat);
+        }
+        Ok(())
+    }
+
+    /// Reads a received byte, non-blocking
+    pub fn read(&mut self) -> nb::Result<u8, Error> {
+        self.serial.rx.read()
+    }
+
+    /// Sets the guard time, in bit durations
+    pub fn set_guard_time(&mut self, guard_time: u8) {
+        let usart = unsafe { &*USART::ptr() };
+        usart.gtpr.modify(|_, w| unsafe { w.gt().bits(guard_time) });
+    }
+
+    /// Leaves smartcard mode and returns the USART and the pins
+    pub fn release(self) -> (USART, (IOPIN, CKPIN)) {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr3.modify(|_, w| {
+            w.nack().clear_bit();
+            w.scen().clear_bit()
+        });
+        usart.cr2.modify(|_, w| w.clken().clear_bit());
+        usart.gtpr.reset();
+        let (usart, (io, ())) = self.serial.release();
+        (usart, (io, self.ck))
+    }
+}
+
+impl<USART: Instance, IOPIN, CKPIN> embedded_io::ErrorType for Smartcard<USART, IOPIN, CKPIN> {
+    type Error = Error;
+}
+
+impl<USART: Instance, IOPIN, CKPIN> embedded_io::Read for Smartcard<USART, IOPIN, CKPIN> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = nb::block!(Smartcard::read(self))?;
+        let mut count = 1;
+        while count < buf.len() {
+            match Smartcard::read(self) {
+                Ok(byte) => buf[count] = byte,
+                Err(nb::Error::WouldBlock) => break,
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+}
+
+impl<USART: Instance, IOPIN, CKPIN> embedded_io::Write for Smartcard<USART, IOPIN, CKPIN> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.bwrite_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        // writes return once the last byte is sent
+        Ok(())
+    }
+}
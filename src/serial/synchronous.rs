@@ -0,0 +1,159 @@
+//! Synchronous mode
+//!
+//! With `CLKEN` set the USART outputs a clock on its CK pin and works as an SPI master: TX is
+//! MOSI and RX is MISO. It has no slave select, which is driven by a GPIO. The clock can run at
+//! up to 1/16 of the USART clock.
+//!
+//! The USART shifts the least significant bit first, [Synchronous] reverses the bits of each
+//! byte to send the most significant bit first, see [Synchronous::bit_format].
+//!
+//! ```rust
+//! let mode = Mode {
+//!     polarity: Polarity::IdleLow,
+//!     phase: Phase::CaptureOnFirstTransition,
+//! };
+//! let mut spi = p.USART2.synchronous((ck, miso, mosi), mode, 1.MHz(), &clocks)?;
+//! cs.set_low();
+//! spi.transfer_in_place(&mut buffer)?;
+//! cs.set_high();
+//! ```
+
+use super::{CkPin, Config, ConfigError, Error, Instance, RxPin, Serial, TxPin};
+use crate::rcc::Clocks;
+use crate::spi::{Mode, Phase, Polarity, SpiBitFormat};
+use crate::time::{Hertz, U32Ext};
+
+use embedded_hal::spi::ErrorKind;
+
+/// Serial port in synchronous master mode, see the [module](self) documentation
+pub struct Synchronous<USART: Instance, CKPIN, RXPIN, TXPIN> {
+    serial: Serial<USART, TXPIN, RXPIN>,
+    ck: CKPIN,
+    format: SpiBitFormat,
+}
+
+impl<USART: Instance, TXPIN, RXPIN> Serial<USART, TXPIN, RXPIN> {
+    /// Configures the USART as SPI master with the clock `pins.0`, receiving on `pins.1` and
+    /// transmitting on `pins.2`
+    ///
+    /// Returns an error if `freq` is higher than 1/16 of the USART clock. The clock is not
+    /// checked against a tolerance.
+    pub fn synchronous<CKPIN>(
+        usart: USART,
+        pins: (CKPIN, RXPIN, TXPIN),
+        mode: Mode,
+        freq: Hertz,
+        clocks: &Clocks,
+    ) -> Result<Synchronous<USART, CKPIN, RXPIN, TXPIN>, ConfigError>
+    where
+        CKPIN: CkPin<USART>,
+        RXPIN: RxPin<USART>,
+        TXPIN: TxPin<USART>,
+    {
+        let (ck, rx, tx) = pins;
+        let config = Config::default()
+            .baudrate(freq.raw().bps())
+            .baud_tolerance(u32::MAX);
+        let serial = Self::_new(usart, (tx, rx), config, clocks)?;
+        let usart = unsafe { &*USART::ptr() };
+        // the clock settings may only change while the USART is disabled
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr2.modify(|_, w| {
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh);
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition);
+            // clock the last data bit too
+            w.lbcl().set_bit();
+            w.clken().set_bit()
+        });
+        usart.cr1.modify(|_, w| w.ue().enabled());
+        Ok(Synchronous {
+            serial,
+            ck,
+            format: SpiBitFormat::MsbFirst,
+        })
+    }
+}
+
+impl<USART: Instance, CKPIN, RXPIN, TXPIN> Synchronous<USART, CKPIN, RXPIN, TXPIN> {
+    /// Selects the bit order, the default is [`SpiBitFormat::MsbFirst`]
+    pub fn bit_format(&mut self, format: SpiBitFormat) {
+        self.format = format;
+    }
+
+    /// Sends a byte and returns the byte received meanwhile
+    pub fn transfer_byte(&mut self, byte: u8) -> Result<u8, Error> {
+        nb::block!(self.serial.tx.write_u8(self.encode(byte)))?;
+        let received = nb::block!(self.serial.rx.read())?;
+        Ok(self.encode(received))
+    }
+
+    /// Leaves synchronous mode and returns the USART and the pins
+    pub fn release(self) -> (USART, (CKPIN, RXPIN, TXPIN)) {
+        let usart = unsafe { &*USART::ptr() };
+        usart.cr1.modify(|_, w| w.ue().disabled());
+        usart.cr2.modify(|_, w| w.clken().clear_bit());
+        let (usart, (tx, rx)) = self.serial.release();
+        (usart, (self.ck, rx, tx))
+    }
+
+    fn encode(&self, byte: u8) -> u8 {
+        match self.format {
+            SpiBitFormat::MsbFirst => byte.reverse_bits(),
+            SpiBitFormat::LsbFirst => byte,
+        }
+    }
+}
+
+impl embedded_hal::spi::Error for Error {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Overrun => ErrorKind::Overrun,
+            _ => ErrorKind::Other,
+        }
+    }
+}
+
+impl<USART: Instance, CKPIN, RXPIN, TXPIN> embedded_hal::spi::ErrorType
+    for Synchronous<USART, CKPIN, RXPIN, TXPIN>
+{
+    type Error = Error;
+}
+
+impl<USART: Instance, CKPIN, RXPIN, TXPIN> embedded_hal::spi::SpiBus
+    for Synchronous<USART, CKPIN, RXPIN, TXPIN>
+{
+    fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.transfer_byte(0)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+        for &word in words {
+            self.transfer_byte(word)?;
+        }
+        Ok(())
+    }
+
+    fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+        for i in 0..read.len().max(write.len()) {
+            let received = self.transfer_byte(write.get(i).copied().unwrap_or(0))?;
+            if let Some(word) = read.get_mut(i) {
+                *word = received;
+            }
+        }
+        Ok(())
+    }
+
+    fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+        for word in words {
+            *word = self.transfer_byte(*word)?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        nb::block!(self.serial.tx.flush())
+    }
+}
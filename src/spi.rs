@@ -56,10 +56,20 @@ use crate::rcc::{BusClock, Clocks, Enable, Reset};
 
 use crate::time::Hertz;
 
+pub use crate::spi_prescaler::SpiPrescaler;
+
+mod bidi;
+pub use bidi::{SpiBidi, SpiSlaveBidi};
+mod device;
+pub use device::{BusConfig, CriticalSectionDevice, DeviceConfig, ExclusiveDevice};
 #[cfg(feature = "with-dma")]
 pub mod dma;
 mod hal_02;
 mod hal_1;
+mod hal_async;
+pub use hal_async::on_interrupt;
+mod responder;
+pub use responder::{NssMode, RegisterMap, Responder};
 
 /// Clock polarity
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -116,6 +126,10 @@ pub enum Error {
     Overrun,
     /// Mode fault occurred
     ModeFault,
+    /// A frame was not received in time
+    Timeout,
+    /// A DMA channel stopped on a bus error
+    Dma,
 }
 
 /// Extension trait for SPI devices
@@ -170,6 +184,32 @@ pub trait SpiExt: Sized + Instance {
     {
         Self::spi_slave(self, pins, mode).frame_size_16bit()
     }
+    /// Use SPI in bidirectional master mode with 8 bit words, see [SpiBidi]
+    fn spi_bidi<SCKPIN, MOSIPIN>(
+        self,
+        pins: (SCKPIN, MOSIPIN),
+        mode: Mode,
+        freq: Hertz,
+        clocks: &Clocks,
+    ) -> SpiBidi<Self, SCKPIN, MOSIPIN, u8>
+    where
+        SCKPIN: SckPin<Self>,
+        MOSIPIN: MosiPin<Self>,
+    {
+        Spi::new_bidi(self, pins, mode, freq, clocks)
+    }
+    /// Use SPI in bidirectional slave mode with 8 bit words, see [SpiSlaveBidi]
+    fn spi_slave_bidi<SCKPIN, MISOPIN>(
+        self,
+        pins: (SCKPIN, MISOPIN),
+        mode: Mode,
+    ) -> SpiSlaveBidi<Self, SCKPIN, MISOPIN, u8>
+    where
+        SCKPIN: SckPin<Self>,
+        MISOPIN: MisoPin<Self>,
+    {
+        SpiSlave::new_bidi(self, pins, mode)
+    }
 }
 
 impl<SPI: Instance> SpiExt for SPI {
@@ -267,11 +307,59 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, W> DerefMut
 pub trait Instance:
     crate::Sealed + Deref<Target = crate::pac::spi1::RegisterBlock> + Enable + Reset + BusClock
 {
+    #[doc(hidden)]
+    fn ptr() -> *const crate::pac::spi1::RegisterBlock;
+    #[doc(hidden)]
+    fn state() -> &'static hal_async::State;
 }
 
-impl Instance for pac::SPI1 {}
-#[cfg(feature = "py32f030")]
-impl Instance for pac::SPI2 {}
+macro_rules! inst {
+    ($($SPIX:ty;)+) => {
+        $(
+            impl Instance for $SPIX {
+                fn ptr() -> *const crate::pac::spi1::RegisterBlock {
+                    <$SPIX>::ptr()
+                }
+                fn state() -> &'static hal_async::State {
+                    static STATE: hal_async::State = hal_async::State::new();
+                    &STATE
+                }
+            }
+        )+
+    };
+}
+
+inst! {
+    pac::SPI1;
+}
+
+#[cfg(any(feature = "py32f030", feature = "py32f072"))]
+inst! {
+    pac::SPI2;
+}
+
+/// Switch the data frame between 8 and 16 bits, the peripheral must be disabled
+fn set_frame_size<SPI: Instance>(spi: &SPI, bits16: bool) {
+    // py32f072 selects the frame size with DDF in CR1, the others with DS in CR2
+    #[cfg(not(feature = "py32f072"))]
+    spi.cr2.modify(|_, w| w.ds().bit(bits16));
+    #[cfg(feature = "py32f072")]
+    spi.cr1.modify(|_, w| w.ddf().bit(bits16));
+    // fifo reception threshold follows the frame size
+    #[cfg(not(feature = "py32f002b"))]
+    spi.cr2.modify(|_, w| {
+        if bits16 {
+            w.frxth().half()
+        } else {
+            w.frxth().quarter()
+        }
+    });
+}
+
+/// `BR` value for the clock `freq` from `pclk`
+fn baud_rate_divider(pclk: Hertz, freq: Hertz) -> u8 {
+    SpiPrescaler::new(pclk.raw(), freq.raw()).br()
+}
 
 /// trait for SPI Sck pins
 pub trait SckPin<SPI> {}
@@ -280,109 +368,53 @@ pub trait MisoPin<SPI> {}
 /// trait for SPI MOSI pins
 pub trait MosiPin<SPI> {}
 
-macro_rules! spi_pins {
-    ($($SPI:ident => {
-        sck => [$($sck:ty),+ $(,)*],
-        miso => [$($miso:ty),+ $(,)*],
-        mosi => [$($mosi:ty),+ $(,)*],
-    })+) => {
-        $(
-            $(
-                impl SckPin<crate::pac::$SPI> for $sck {}
-            )+
-            $(
-                impl MisoPin<crate::pac::$SPI> for $miso {}
-            )+
-            $(
-                impl MosiPin<crate::pac::$SPI> for $mosi {}
-            )+
-        )+
+// afgen:begin spi
+// Generated by tools/afgen.py from tools/af/*.csv, do not edit by hand
+
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002a",
+    feature = "py32f002b"
+))]
+af_pins! {
+    SPI1 => {
+        MosiPin => [PA7<AF0>],
     }
 }
 
 #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002a"))]
-spi_pins! {
+af_pins! {
     SPI1 => {
-        sck =>  [
-            gpioa::PA1<Alternate<AF0>>,
-            gpioa::PA2<Alternate<AF10>>,
-            gpioa::PA5<Alternate<AF0>>,
-            gpiob::PB3<Alternate<AF0>>,
-        ],
-        miso => [
-            gpioa::PA0<Alternate<AF10>>,
-            gpioa::PA3<Alternate<AF0>>,
-            gpioa::PA6<Alternate<AF0>>,
-            gpioa::PA7<Alternate<AF10>>,
-            gpioa::PA13<Alternate<AF10>>,
-            gpiob::PB4<Alternate<AF0>>,
-        ],
-        mosi => [
-            gpioa::PA1<Alternate<AF10>>,
-            gpioa::PA2<Alternate<AF0>>,
-            gpioa::PA3<Alternate<AF10>>,
-            gpioa::PA7<Alternate<AF0>>,
-            gpioa::PA12<Alternate<AF0>>,
-            gpiob::PB5<Alternate<AF0>>,
-        ],
+        SckPin => [PA1<AF0>, PA2<AF10>, PA5<AF0>, PB3<AF0>],
+        MisoPin => [PA0<AF10>, PA3<AF0>, PA6<AF0>, PA7<AF10>, PA13<AF10>, PB4<AF0>],
+        MosiPin => [PA1<AF10>, PA2<AF0>, PA3<AF10>, PA12<AF0>, PB5<AF0>],
     }
 }
 
 #[cfg(feature = "py32f030")]
-spi_pins! {
+af_pins! {
     SPI1 => {
-        sck =>  [
-            gpioa::PA9<Alternate<AF10>>,
-        ],
-        miso => [
-            gpioa::PA11<Alternate<AF0>>,
-        ],
-        mosi => [
-            gpioa::PA8<Alternate<AF10>>,
-        ],
+        SckPin => [PA9<AF10>],
+        MisoPin => [PA11<AF0>],
+        MosiPin => [PA8<AF10>],
     }
     SPI2 => {
-        sck =>  [
-            gpioa::PA1<Alternate<AF0>>,
-            gpiob::PB2<Alternate<AF1>>,
-            gpiob::PB8<Alternate<AF1>>,
-            gpiof::PF0<Alternate<AF3>>,
-        ],
-        miso => [
-            gpioa::PA3<Alternate<AF0>>,
-            gpioa::PA9<Alternate<AF0>>,
-            gpiob::PB6<Alternate<AF3>>,
-            gpiof::PF1<Alternate<AF3>>,
-            gpiof::PF3<Alternate<AF3>>,
-        ],
-        mosi => [
-            gpioa::PA4<Alternate<AF2>>,
-            gpioa::PA10<Alternate<AF0>>,
-            gpiob::PB7<Alternate<AF1>>,
-            gpiof::PF2<Alternate<AF3>>,
-        ],
+        SckPin => [PA1<AF0>, PB2<AF1>, PB8<AF1>, PF0<AF3>],
+        MisoPin => [PA3<AF0>, PA9<AF0>, PB6<AF3>, PF1<AF3>, PF3<AF3>],
+        MosiPin => [PA4<AF2>, PA10<AF0>, PB7<AF1>, PF2<AF3>],
     }
 }
 
 #[cfg(feature = "py32f002b")]
-spi_pins! {
+af_pins! {
     SPI1 => {
-        sck =>  [
-            gpiob::PB0<Alternate<AF0>>,
-            gpiob::PB2<Alternate<AF0>>,
-        ],
-        miso => [
-            gpioa::PA1<Alternate<AF0>>,
-            gpiob::PB6<Alternate<AF2>>,
-            gpioc::PC1<Alternate<AF0>>,
-        ],
-        mosi => [
-            gpioa::PA0<Alternate<AF0>>,
-            gpioa::PA7<Alternate<AF0>>,
-            gpiob::PB7<Alternate<AF0>>,
-        ],
+        SckPin => [PB0<AF0>, PB2<AF0>],
+        MisoPin => [PA1<AF0>, PB6<AF2>, PC1<AF0>],
+        MosiPin => [PA0<AF0>, PB7<AF0>],
     }
 }
+// afgen:end spi
 
 impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, WIDTH: Copy>
     Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, WIDTH>
@@ -420,17 +452,7 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, WIDTH: Copy>
         /* Make sure the SPI unit is disabled so we can configure it */
         spi.cr1.modify(|_, w| w.spe().clear_bit());
 
-        let br = match clocks.pclk().raw() / speed.into().raw() {
-            0 => unreachable!(),
-            1..=2 => 0b000,
-            3..=5 => 0b001,
-            6..=11 => 0b010,
-            12..=23 => 0b011,
-            24..=47 => 0b100,
-            48..=95 => 0b101,
-            96..=191 => 0b110,
-            _ => 0b111,
-        };
+        let br = baud_rate_divider(clocks.pclk(), speed.into());
 
         // mstr: master configuration
         // lsbfirst: MSB first
@@ -459,6 +481,13 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, WIDTH: Copy>
             pins,
         }
     }
+    /// Sets the clock to the fastest division of the peripheral clock of at most `freq`, once the
+    /// current transfer is over, and returns the clock achieved
+    pub fn set_frequency(&mut self, freq: Hertz, clocks: &Clocks) -> Hertz {
+        let prescaler = SpiPrescaler::new(clocks.pclk().raw(), freq.raw());
+        self.inner.modify_disabled(|w| w.br().bits(prescaler.br()));
+        Hertz::from_raw(prescaler.achieved())
+    }
     /// Release the SPI instance and any pins used
     pub fn release(self) -> (SPI, (Option<SCKPIN>, Option<MISOPIN>, Option<MOSIPIN>)) {
         (self.inner.spi, self.pins)
@@ -494,6 +523,9 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, WIDTH: Copy>
 
         // disable SS output
         spi.cr2.write(|w| w.ssoe().clear_bit());
+        // fifo reception threshold set to 1/4
+        #[cfg(not(feature = "py32f002b"))]
+        spi.cr2.modify(|_, w| w.frxth().quarter());
 
         spi.cr1.write(|w| {
             // clock phase from config
@@ -535,6 +567,35 @@ impl<SPI: Instance, W: Copy> SpiInner<SPI, W> {
             .modify(|_, w| w.lsbfirst().bit(matches!(format, SpiBitFormat::LsbFirst)));
     }
 
+    /// Select which frame format is used for data transfers, once the current transfer is over
+    pub fn set_bit_format(&mut self, format: SpiBitFormat) {
+        self.modify_disabled(|w| w.lsbfirst().bit(matches!(format, SpiBitFormat::LsbFirst)));
+    }
+
+    /// Sets the clock polarity and phase, once the current transfer is over
+    pub fn set_mode(&mut self, mode: impl Into<Mode>) {
+        let mode = mode.into();
+        self.modify_disabled(|w| {
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh);
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition)
+        });
+    }
+
+    /// Modifies CR1 with `f` while the SPI is disabled, waiting for the current transfer to end
+    fn modify_disabled<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut pac::spi1::cr1::W) -> &mut pac::spi1::cr1::W,
+    {
+        // a master sends the words left in the transmit fifo first
+        if self.spi.cr1.read().mstr().bit_is_set() {
+            while !self.is_tx_empty() {}
+        }
+        while self.is_busy() {}
+        self.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        self.spi.cr1.modify(|_, w| f(w));
+        self.spi.cr1.modify(|_, w| w.spe().set_bit());
+    }
+
     /// Starts listening to the SPI by enabling the _Received data
     /// ready to be read (RXNE)_ interrupt and _Transmit data
     /// register empty (TXE)_ interrupt
@@ -586,12 +647,7 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> Spi<SPI, SCKPIN, MISOPIN, MOSIPIN,
     /// Converts from 8bit dataframe to 16bit.
     pub fn frame_size_16bit(self) -> Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u16> {
         self.spi.cr1.modify(|_, w| w.spe().clear_bit());
-        self.spi.cr2.modify(|_, w| {
-            w.ds().set_bit();
-            #[cfg(not(feature = "py32f002b"))]
-            w.frxth().half();
-            w
-        });
+        set_frame_size(&self.spi, true);
         self.spi.cr1.modify(|_, w| w.spe().set_bit());
         Spi {
             inner: SpiInner::new(self.inner.spi),
@@ -604,12 +660,7 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> SpiSlave<SPI, SCKPIN, MISOPIN, MOS
     /// Converts from 8bit dataframe to 16bit.
     pub fn frame_size_16bit(self) -> SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u16> {
         self.spi.cr1.modify(|_, w| w.spe().clear_bit());
-        self.spi.cr2.modify(|_, w| {
-            w.ds().set_bit();
-            #[cfg(not(feature = "py32f002b"))]
-            w.frxth().half();
-            w
-        });
+        set_frame_size(&self.spi, true);
         self.spi.cr1.modify(|_, w| w.spe().set_bit());
         SpiSlave {
             inner: SpiInner::new(self.inner.spi),
@@ -620,14 +671,9 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> SpiSlave<SPI, SCKPIN, MISOPIN, MOS
 
 impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u16> {
     /// Converts from 16bit dataframe to 8bit.
-    pub fn frame_size_8bit(self) -> Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u16> {
+    pub fn frame_size_8bit(self) -> Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8> {
         self.spi.cr1.modify(|_, w| w.spe().clear_bit());
-        self.spi.cr2.modify(|_, w| {
-            w.ds().clear_bit();
-            #[cfg(not(feature = "py32f002b"))]
-            w.frxth().quarter();
-            w
-        });
+        set_frame_size(&self.spi, false);
         self.spi.cr1.modify(|_, w| w.spe().set_bit());
         Spi {
             inner: SpiInner::new(self.inner.spi),
@@ -640,12 +686,7 @@ impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> SpiSlave<SPI, SCKPIN, MISOPIN, MOS
     /// Converts from 16bit dataframe to 8bit.
     pub fn frame_size_8bit(self) -> SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8> {
         self.spi.cr1.modify(|_, w| w.spe().clear_bit());
-        self.spi.cr2.modify(|_, w| {
-            w.ds().clear_bit();
-            #[cfg(not(feature = "py32f002b"))]
-            w.frxth().quarter();
-            w
-        });
+        set_frame_size(&self.spi, false);
         self.spi.cr1.modify(|_, w| w.spe().set_bit());
         SpiSlave {
             inner: SpiInner::new(self.inner.spi),
@@ -710,6 +751,14 @@ where
     SPI: Instance,
     W: Copy,
 {
+    /// Drops the words left in the receive fifo by writes and clears the overrun flag
+    fn drain_rx(&mut self) {
+        while self.is_rx_not_empty() {
+            let _ = self.read_data_reg();
+        }
+        let _ = self.spi.sr.read();
+    }
+
     /// Read a word from the SPI device, non-blocking
     pub fn read_nonblocking(&mut self) -> nb::Result<W, Error> {
         let sr = self.spi.sr.read();
@@ -0,0 +1,271 @@
+//! Bidirectional 3-wire mode
+//!
+//! With `BIDIMODE` set, the data travels both ways on a single line: MOSI for a master, MISO for
+//! a slave. `BIDIOE` selects the direction, [SpiBidi::read] and [SpiBidi::write] switch it before
+//! each transfer.
+//!
+//! A master receiving in this mode clocks frames for as long as the SPI is enabled, so
+//! [SpiBidi::read] waits one SCK period once the second to last frame is received, then
+//! disables it while the last frame is shifted in, with interrupts disabled to hit that window.
+//! At high clock rates the CPU may still lag behind and a few extra frames are clocked, they are
+//! dropped. Between transfers the master is left in the transmit direction.
+//!
+//! A slave is left in the receive direction, so it only drives the line during
+//! [SpiSlaveBidi::write].
+//!
+//! ```rust
+//! let mut spi = Spi::new_bidi(p.SPI1, (sck, sdio), MODE_3.into(), 1.MHz(), &clocks);
+//! cs.set_low();
+//! spi.write(&[0x80 | WHO_AM_I])?;
+//! spi.read(&mut id)?;
+//! cs.set_high();
+//! ```
+
+use super::{
+    baud_rate_divider, set_frame_size, Error, Instance, MisoPin, Mode, MosiPin, Phase, Polarity,
+    SckPin, Spi, SpiInner, SpiReadWrite, SpiSlave,
+};
+use crate::rcc::Clocks;
+use crate::time::Hertz;
+
+/// Spi in bidirectional master mode, see the [module](self) documentation
+pub struct SpiBidi<SPI: Instance, SCKPIN, MOSIPIN, W> {
+    inner: SpiInner<SPI, W>,
+    pins: (SCKPIN, MOSIPIN),
+    // core clock cycles in one SCK period
+    sck_cycles: u32,
+}
+
+/// Spi in bidirectional slave mode, see the [module](self) documentation
+pub struct SpiSlaveBidi<SPI: Instance, SCKPIN, MISOPIN, W> {
+    inner: SpiInner<SPI, W>,
+    pins: (SCKPIN, MISOPIN),
+}
+
+impl<SPI: Instance, SCKPIN, MOSIPIN> Spi<SPI, SCKPIN, (), MOSIPIN, u8> {
+    /// Creates a master with 8 bit words, transferring data both ways on the MOSI pin
+    pub fn new_bidi(
+        spi: SPI,
+        pins: (SCKPIN, MOSIPIN),
+        mode: Mode,
+        freq: Hertz,
+        clocks: &Clocks,
+    ) -> SpiBidi<SPI, SCKPIN, MOSIPIN, u8>
+    where
+        SCKPIN: SckPin<SPI>,
+        MOSIPIN: MosiPin<SPI>,
+    {
+        // enable and reset SPI
+        let rcc = unsafe { &(*crate::pac::RCC::ptr()) };
+        SPI::enable(rcc);
+        SPI::reset(rcc);
+
+        let br = baud_rate_divider(clocks.pclk(), freq);
+        spi.cr1.write(|w| {
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition);
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh);
+            w.mstr().set_bit();
+            w.br().bits(br);
+            w.lsbfirst().clear_bit();
+            w.ssm().set_bit();
+            w.ssi().set_bit();
+            w.rxonly().clear_bit();
+            // bidimode: 1-line bidirectional, transmitting
+            w.bidimode().set_bit();
+            w.bidioe().set_bit();
+            w.spe().set_bit()
+        });
+        // fifo reception threshold set to 1/4
+        #[cfg(not(feature = "py32f002b"))]
+        spi.cr2.modify(|_, w| w.frxth().quarter());
+
+        SpiBidi {
+            inner: SpiInner::new(spi),
+            pins,
+            sck_cycles: u32::from(clocks.ppre()) << (br + 1),
+        }
+    }
+}
+
+impl<SPI: Instance, SCKPIN, MISOPIN> SpiSlave<SPI, SCKPIN, MISOPIN, (), u8> {
+    /// Creates a slave with 8 bit words, transferring data both ways on the MISO pin
+    pub fn new_bidi(
+        spi: SPI,
+        pins: (SCKPIN, MISOPIN),
+        mode: Mode,
+    ) -> SpiSlaveBidi<SPI, SCKPIN, MISOPIN, u8>
+    where
+        SCKPIN: SckPin<SPI>,
+        MISOPIN: MisoPin<SPI>,
+    {
+        // enable and reset SPI
+        let rcc = unsafe { &(*crate::pac::RCC::ptr()) };
+        SPI::enable(rcc);
+        SPI::reset(rcc);
+
+        // disable SS output
+        spi.cr2.write(|w| w.ssoe().clear_bit());
+        spi.cr1.write(|w| {
+            w.cpha().bit(mode.phase == Phase::CaptureOnSecondTransition);
+            w.cpol().bit(mode.polarity == Polarity::IdleHigh);
+            w.mstr().clear_bit();
+            w.lsbfirst().clear_bit();
+            w.ssm().set_bit();
+            w.ssi().clear_bit();
+            w.rxonly().clear_bit();
+            // bidimode: 1-line bidirectional, receiving
+            w.bidimode().set_bit();
+            w.bidioe().clear_bit();
+            w.spe().set_bit()
+        });
+        // fifo reception threshold set to 1/4
+        #[cfg(not(feature = "py32f002b"))]
+        spi.cr2.modify(|_, w| w.frxth().quarter());
+
+        SpiSlaveBidi {
+            inner: SpiInner::new(spi),
+            pins,
+        }
+    }
+}
+
+impl<SPI: Instance, W: Copy> SpiInner<SPI, W> {
+    /// Sets the direction of the data line, disabling the SPI meanwhile
+    fn set_output(&mut self, output: bool) {
+        if self.spi.cr1.read().bidioe().bit() != output {
+            self.spi.cr1.modify(|_, w| w.spe().clear_bit());
+            self.spi.cr1.modify(|_, w| w.bidioe().bit(output));
+            self.spi.cr1.modify(|_, w| w.spe().set_bit());
+        }
+    }
+
+    /// Waits for a received word
+    fn receive_word(&mut self) -> Result<W, Error> {
+        nb::block!(self.read_nonblocking())
+    }
+
+    /// Waits for a received word for at most `cycles` core clock cycles
+    fn receive_word_timeout(&mut self, cycles: u32) -> Result<W, Error> {
+        // each poll takes more than one cycle
+        for _ in 0..cycles {
+            match self.read_nonblocking() {
+                Err(nb::Error::WouldBlock) => {}
+                Err(nb::Error::Other(e)) => return Err(e),
+                Ok(word) => return Ok(word),
+            }
+        }
+        Err(Error::Timeout)
+    }
+
+    /// Receives `words` as a master in the receive direction, see the [module](self)
+    /// documentation
+    ///
+    /// `sck_cycles` is the number of core clock cycles in one SCK period.
+    fn bidi_master_read(&mut self, words: &mut [W], sck_cycles: u32) -> Result<(), Error> {
+        let Some((last, words)) = words.split_last_mut() else {
+            return Ok(());
+        };
+        // the clock starts as soon as the SPI is enabled in the receive direction
+        self.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        self.drain_rx();
+        self.spi.cr1.modify(|_, w| w.bidioe().clear_bit());
+        let result = cortex_m::interrupt::free(|_| {
+            self.spi.cr1.modify(|_, w| w.spe().set_bit());
+            let received = words
+                .iter_mut()
+                .try_for_each(|word| self.receive_word().map(|w| *word = w));
+            // the last frame is being shifted in one SCK period after the previous one is
+            // received, stopping then ends the clock after it
+            cortex_m::asm::delay(sck_cycles);
+            self.spi.cr1.modify(|_, w| w.spe().clear_bit());
+            received
+        });
+        // the last frame takes at most 16 SCK periods
+        let result = result.and_then(|_| {
+            *last = self.receive_word_timeout(sck_cycles * 17)?;
+            Ok(())
+        });
+        while self.is_busy() {}
+        self.drain_rx();
+        self.spi.cr1.modify(|_, w| w.bidioe().set_bit());
+        self.spi.cr1.modify(|_, w| w.spe().set_bit());
+        result
+    }
+}
+
+impl<SPI: Instance, SCKPIN, MOSIPIN, W: Copy> SpiBidi<SPI, SCKPIN, MOSIPIN, W> {
+    /// Receives `words` from the device
+    pub fn read(&mut self, words: &mut [W]) -> Result<(), Error> {
+        self.inner.bidi_master_read(words, self.sck_cycles)
+    }
+
+    /// Sends `words` to the device, blocking until the last one is sent
+    pub fn write(&mut self, words: &[W]) -> Result<(), Error> {
+        self.inner.set_output(true);
+        self.inner.spi_write(words)
+    }
+
+    /// Returns true if a transfer is in progress
+    pub fn is_busy(&self) -> bool {
+        self.inner.is_busy()
+    }
+
+    /// Release the SPI instance and the pins
+    pub fn release(self) -> (SPI, (SCKPIN, MOSIPIN)) {
+        self.inner.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        (self.inner.spi, self.pins)
+    }
+}
+
+impl<SPI: Instance, SCKPIN, MOSIPIN> SpiBidi<SPI, SCKPIN, MOSIPIN, u8> {
+    /// Converts from 8bit dataframe to 16bit.
+    pub fn frame_size_16bit(self) -> SpiBidi<SPI, SCKPIN, MOSIPIN, u16> {
+        self.inner.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        set_frame_size(&self.inner.spi, true);
+        self.inner.spi.cr1.modify(|_, w| w.spe().set_bit());
+        SpiBidi {
+            inner: SpiInner::new(self.inner.spi),
+            pins: self.pins,
+            sck_cycles: self.sck_cycles,
+        }
+    }
+}
+
+impl<SPI: Instance, SCKPIN, MISOPIN, W: Copy> SpiSlaveBidi<SPI, SCKPIN, MISOPIN, W> {
+    /// Receives `words` from the master, blocking until they are clocked in
+    pub fn read(&mut self, words: &mut [W]) -> Result<(), Error> {
+        self.inner.set_output(false);
+        words
+            .iter_mut()
+            .try_for_each(|word| self.inner.receive_word().map(|w| *word = w))
+    }
+
+    /// Sends `words` to the master, blocking until they are clocked out
+    ///
+    /// The slave returns to the receive direction afterwards, releasing the line.
+    pub fn write(&mut self, words: &[W]) -> Result<(), Error> {
+        self.inner.set_output(true);
+        let result = self.inner.spi_write(words);
+        self.inner.set_output(false);
+        result
+    }
+
+    /// Release the SPI instance and the pins
+    pub fn release(self) -> (SPI, (SCKPIN, MISOPIN)) {
+        self.inner.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        (self.inner.spi, self.pins)
+    }
+}
+
+impl<SPI: Instance, SCKPIN, MISOPIN> SpiSlaveBidi<SPI, SCKPIN, MISOPIN, u8> {
+    /// Converts from 8bit dataframe to 16bit.
+    pub fn frame_size_16bit(self) -> SpiSlaveBidi<SPI, SCKPIN, MISOPIN, u16> {
+        self.inner.spi.cr1.modify(|_, w| w.spe().clear_bit());
+        set_frame_size(&self.inner.spi, true);
+        self.inner.spi.cr1.modify(|_, w| w.spe().set_bit());
+        SpiSlaveBidi {
+            inner: SpiInner::new(self.inner.spi),
+            pins: self.pins,
+        }
+    }
+}
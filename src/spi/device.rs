@@ -0,0 +1,222 @@
+//! SPI devices with a managed chip select
+//!
+//! An [`embedded_hal::spi::SpiDevice`] drives the chip select of one device around each
+//! transaction, and applies the mode and clock of the device to the bus first, so devices with
+//! different settings can share it:
+//!
+//! - [ExclusiveDevice] owns the bus, for a single device
+//! - [CriticalSectionDevice] borrows the bus from a [`Mutex`], for several devices, possibly
+//!   used from interrupt handlers. Interrupts are disabled during each transaction.
+//!
+//! ```rust
+//! let spi = p.SPI1.spi((Some(sck), Some(miso), Some(mosi)), MODE_0.into(), 8.MHz(), &clocks);
+//! let bus = Mutex::new(RefCell::new(spi));
+//! let rfid_config = DeviceConfig::new(MODE_0, 1.MHz(), &clocks);
+//! let mut rfid = CriticalSectionDevice::new(&bus, nss_rfid, delay_rfid, rfid_config);
+//! let flash_config = DeviceConfig::new(MODE_3, 8.MHz(), &clocks);
+//! let mut flash = CriticalSectionDevice::new(&bus, nss_flash, delay_flash, flash_config);
+//! flash.transaction(&mut [
+//!     Operation::Write(&[0x03, 0x00, 0x10, 0x00]),
+//!     Operation::Read(&mut page),
+//! ])?;
+//! ```
+
+use super::{baud_rate_divider, Instance, Mode, Phase, Polarity, Spi};
+use crate::rcc::Clocks;
+use crate::time::Hertz;
+
+use core::cell::RefCell;
+use cortex_m::interrupt::Mutex;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::{ErrorType, Operation, SpiBus, SpiDevice};
+
+/// Mode and clock of a device
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DeviceConfig {
+    mode: Mode,
+    br: u8,
+}
+
+impl DeviceConfig {
+    /// Creates the configuration of a device using `mode` with the fastest clock of at most
+    /// `freq`, or the slowest clock if `freq` is below it
+    pub fn new(mode: impl Into<Mode>, freq: Hertz, clocks: &Clocks) -> Self {
+        Self {
+            mode: mode.into(),
+            br: baud_rate_divider(clocks.pclk(), freq),
+        }
+    }
+
+    /// The SPI mode
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+}
+
+/// SPI bus whose mode and clock can change between transactions
+pub trait BusConfig {
+    /// Applies `config`, unless the bus already uses it
+    fn configure(&mut self, config: &DeviceConfig);
+}
+
+impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, W: Copy> BusConfig
+    for Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, W>
+{
+    fn configure(&mut self, config: &DeviceConfig) {
+        let cpol = config.mode.polarity == Polarity::IdleHigh;
+        let cpha = config.mode.phase == Phase::CaptureOnSecondTransition;
+        let cr1 = self.inner.spi.cr1.read();
+        if cr1.cpol().bit() == cpol && cr1.cpha().bit() == cpha && cr1.br().bits() == config.br {
+            return;
+        }
+        self.inner.modify_disabled(|w| {
+            w.cpol().bit(cpol);
+            w.cpha().bit(cpha);
+            w.br().bits(config.br)
+        });
+    }
+}
+
+/// Runs `operations` on `bus` with `cs` low
+fn transaction<BUS, CS, DELAY, W>(
+    bus: &mut BUS,
+    cs: &mut CS,
+    delay: &mut DELAY,
+    config: &DeviceConfig,
+    operations: &mut [Operation<'_, W>],
+) -> Result<(), BUS::Error>
+where
+    BUS: SpiBus<W> + BusConfig,
+    CS: OutputPin,
+    DELAY: DelayNs,
+    W: Copy + 'static,
+{
+    bus.configure(config);
+    cs.set_low().ok();
+    let result = operations
+        .iter_mut()
+        .try_for_each(|operation| match operation {
+            Operation::Read(words) => bus.read(words),
+            Operation::Write(words) => bus.write(words),
+            Operation::Transfer(read, write) => bus.transfer(read, write),
+            Operation::TransferInPlace(words) => bus.transfer_in_place(words),
+            Operation::DelayNs(ns) => {
+                bus.flush()?;
+                delay.delay_ns(*ns);
+                Ok(())
+            }
+        });
+    // release the chip select even after an error
+    let flushed = bus.flush();
+    cs.set_high().ok();
+    result.and(flushed)
+}
+
+/// SPI device owning the bus, see the [module](self) documentation
+pub struct ExclusiveDevice<BUS, CS, DELAY> {
+    bus: BUS,
+    cs: CS,
+    delay: DELAY,
+    config: DeviceConfig,
+}
+
+impl<BUS, CS: OutputPin, DELAY> ExclusiveDevice<BUS, CS, DELAY> {
+    /// Creates a device on `bus` selected by `cs`, raising it right away
+    ///
+    /// `delay` times the [`Operation::DelayNs`] operations.
+    pub fn new(bus: BUS, mut cs: CS, delay: DELAY, config: DeviceConfig) -> Self {
+        cs.set_high().ok();
+        Self {
+            bus,
+            cs,
+            delay,
+            config,
+        }
+    }
+
+    /// Returns the bus, the chip select and the delay
+    pub fn release(self) -> (BUS, CS, DELAY) {
+        (self.bus, self.cs, self.delay)
+    }
+}
+
+impl<BUS: ErrorType, CS, DELAY> ErrorType for ExclusiveDevice<BUS, CS, DELAY> {
+    type Error = BUS::Error;
+}
+
+impl<BUS, CS, DELAY, W> SpiDevice<W> for ExclusiveDevice<BUS, CS, DELAY>
+where
+    BUS: SpiBus<W> + BusConfig,
+    CS: OutputPin,
+    DELAY: DelayNs,
+    W: Copy + 'static,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, W>]) -> Result<(), Self::Error> {
+        transaction(
+            &mut self.bus,
+            &mut self.cs,
+            &mut self.delay,
+            &self.config,
+            operations,
+        )
+    }
+}
+
+/// SPI device sharing the bus in a [`Mutex`], see the [module](self) documentation
+pub struct CriticalSectionDevice<'a, BUS, CS, DELAY> {
+    bus: &'a Mutex<RefCell<BUS>>,
+    cs: CS,
+    delay: DELAY,
+    config: DeviceConfig,
+}
+
+impl<'a, BUS, CS: OutputPin, DELAY> CriticalSectionDevice<'a, BUS, CS, DELAY> {
+    /// Creates a device on the shared `bus` selected by `cs`, raising it right away
+    ///
+    /// `delay` times the [`Operation::DelayNs`] operations.
+    pub fn new(
+        bus: &'a Mutex<RefCell<BUS>>,
+        mut cs: CS,
+        delay: DELAY,
+        config: DeviceConfig,
+    ) -> Self {
+        cs.set_high().ok();
+        Self {
+            bus,
+            cs,
+            delay,
+            config,
+        }
+    }
+
+    /// Returns the chip select and the delay
+    pub fn release(self) -> (CS, DELAY) {
+        (self.cs, self.delay)
+    }
+}
+
+impl<BUS: ErrorType, CS, DELAY> ErrorType for CriticalSectionDevice<'_, BUS, CS, DELAY> {
+    type Error = BUS::Error;
+}
+
+impl<BUS, CS, DELAY, W> SpiDevice<W> for CriticalSectionDevice<'_, BUS, CS, DELAY>
+where
+    BUS: SpiBus<W> + BusConfig,
+    CS: OutputPin,
+    DELAY: DelayNs,
+    W: Copy + 'static,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, W>]) -> Result<(), Self::Error> {
+        cortex_m::interrupt::free(|cs| {
+            let mut bus = self.bus.borrow(cs).borrow_mut();
+            transaction(
+                &mut *bus,
+                &mut self.cs,
+                &mut self.delay,
+                &self.config,
+                operations,
+            )
+        })
+    }
+}
@@ -4,29 +4,43 @@ use super::*;
 // DMA
 
 use crate::dma::{
-    self, dma1, Ch, DmaExt, PeriphMap, ReadDma, ReadWriteDma, Receive, RxDma, RxTxDma, Transfer,
-    TransferPayload, Transmit, TxDma, WriteDma,
+    self, dma1, Ch, DataSize, DmaExt, PeriphMap, ReadDma, ReadWriteDma, Receive, RxDma, RxTxDma,
+    Transfer, TransferPayload, Transmit, TxDma, WriteDma,
 };
 
 /// Alias for [Spi] in master mode transmitting with DMA on [Ch]
-pub type SpiTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL> =
-    TxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, CHANNEL>;
+pub type SpiTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL, W = u8> =
+    TxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, CHANNEL>;
 /// Alias for [Spi] in master mode receiving with DMA on [Ch]
-pub type SpiRxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL> =
-    RxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, CHANNEL>;
+pub type SpiRxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL, W = u8> =
+    RxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, CHANNEL>;
 /// Alias for [Spi] in master mode transmitting and receiving with DMA on [Ch]
-pub type SpiRxTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, RXCHANNEL, TXCHANNEL> =
-    RxTxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, RXCHANNEL, TXCHANNEL>;
+pub type SpiRxTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, RXCHANNEL, TXCHANNEL, W = u8> =
+    RxTxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, RXCHANNEL, TXCHANNEL>;
 
 /// Alias for [Spi] in slave mode transmitting with DMA on [Ch]
-pub type SpiSlaveTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL> =
-    TxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, CHANNEL>;
+pub type SpiSlaveTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL, W = u8> =
+    TxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, CHANNEL>;
 /// Alias for [Spi] in slave mode receiving with DMA on [Ch]
-pub type SpiSlaveRxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL> =
-    RxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, CHANNEL>;
+pub type SpiSlaveRxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, CHANNEL, W = u8> =
+    RxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, CHANNEL>;
 /// Alias for [Spi] in slave mode transmitting and receiving with DMA on [Ch]
-pub type SpiSlaveRxTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, RXCHANNEL, TXCHANNEL> =
-    RxTxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, RXCHANNEL, TXCHANNEL>;
+pub type SpiSlaveRxTxDma<SPI, SCKPIN, MISOPIN, MOSIPIN, RXCHANNEL, TXCHANNEL, W = u8> =
+    RxTxDma<SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, W>, RXCHANNEL, TXCHANNEL>;
+
+/// Data frame of SPI DMA transfers, [u8] or [u16] after [`Spi::frame_size_16bit`]
+pub trait DmaFrame: Copy + crate::Sealed {
+    /// Data size of the DMA channels
+    const SIZE: DataSize;
+}
+
+impl DmaFrame for u8 {
+    const SIZE: DataSize = DataSize::Bits8;
+}
+
+impl DmaFrame for u16 {
+    const SIZE: DataSize = DataSize::Bits16;
+}
 
 macro_rules! spi_dmarx {
     (
@@ -34,10 +48,10 @@ macro_rules! spi_dmarx {
             $($rxdma:ident,  $slaverxdma:ident: $ch:ty,)+
         }),) => {
 
-        impl<SCKPIN, MISOPIN, MOSIPIN> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in master mode with DMA on [Ch]
             pub fn with_rx_dma<DMA: DmaExt, const C: u8>(self, mut channel: Ch<DMA, C>) ->
-                SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>> {
+                SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 channel.set_map($dmamux);
@@ -48,10 +62,10 @@ macro_rules! spi_dmarx {
                 }
             }
         }
-        impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in slave mode with DMA on [Ch]
             pub fn with_rx_dma<DMA: DmaExt, const C: u8>(self, mut channel: Ch<DMA, C>) ->
-                SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>> {
+                SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 channel.set_map($dmamux);
@@ -65,16 +79,16 @@ macro_rules! spi_dmarx {
 
         $(
             /// Alias for SPI receive instance in master mode with DMA
-            pub type $rxdma<SCKPIN, MISOPIN, MOSIPIN> = SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>;
+            pub type $rxdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> = SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>;
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Receive for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Receive for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 type RxChannel = $ch;
-                type TransmittedWord = u8;
+                type TransmittedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 /// Release the SPI instance, pins, and DMA channel
-                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $ch) {
+                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $ch) {
                     self.stop();
                     // reset the mux to default
                     self.channel.set_map(PeriphMap::Adc);
@@ -84,7 +98,7 @@ macro_rules! spi_dmarx {
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 fn start(&mut self) {
                     self.channel.start();
                 }
@@ -93,9 +107,9 @@ macro_rules! spi_dmarx {
                 }
             }
 
-            impl<B, SCKPIN, MISOPIN, MOSIPIN> ReadDma<B, u8> for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>
+            impl<B, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> ReadDma<B, W> for SpiRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>
             where
-                B: WriteBuffer<Word = u8>,
+                B: WriteBuffer<Word = W>,
             {
                 fn read(mut self, mut buffer: B) -> Transfer<dma::W, B, Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -107,6 +121,7 @@ macro_rules! spi_dmarx {
                     );
                     self.channel.set_memory_address(ptr as u32, true);
                     self.channel.set_transfer_length(len);
+                    self.channel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.channel.ch().cr.modify(|_, w| {
@@ -114,10 +129,6 @@ macro_rules! spi_dmarx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // write to memory
@@ -130,18 +141,18 @@ macro_rules! spi_dmarx {
             }
 
             /// Alias for SPI receive instance in slave mode with DMA
-            pub type $slaverxdma<SCKPIN, MISOPIN, MOSIPIN> =
-                SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>;
+            pub type $slaverxdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> =
+                SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>;
 
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Receive for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Receive for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 type RxChannel = $ch;
-                type TransmittedWord = u8;
+                type TransmittedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 /// Release SPI instance, pins and DMA channel
-                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $ch) {
+                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $ch) {
                     self.stop();
                     // reset the mux to default
                     self.channel.set_map(PeriphMap::Adc);
@@ -151,7 +162,7 @@ macro_rules! spi_dmarx {
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 fn start(&mut self) {
                     self.channel.start();
                 }
@@ -160,9 +171,9 @@ macro_rules! spi_dmarx {
                 }
             }
 
-            impl<B, SCKPIN, MISOPIN, MOSIPIN> ReadDma<B, u8> for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>
+            impl<B, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> ReadDma<B, W> for SpiSlaveRxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>
             where
-                B: WriteBuffer<Word = u8>,
+                B: WriteBuffer<Word = W>,
             {
                 fn read(mut self, mut buffer: B) -> Transfer<dma::W, B, Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -174,6 +185,7 @@ macro_rules! spi_dmarx {
                     );
                     self.channel.set_memory_address(ptr as u32, true);
                     self.channel.set_transfer_length(len);
+                    self.channel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.channel.ch().cr.modify(|_, w| {
@@ -181,10 +193,6 @@ macro_rules! spi_dmarx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // write to memory
@@ -224,10 +232,10 @@ macro_rules! spi_dmatx {
             $($txdma:ident,  $slavetxdma:ident: $ch:ty,)+
         }),) => {
 
-        impl<SCKPIN, MISOPIN, MOSIPIN> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in master mode with DMA on [Ch]
             pub fn with_tx_dma<DMA: DmaExt, const C: u8>(self, mut channel: Ch<DMA, C>) ->
-                SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>> {
+                SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 channel.set_map($dmamux);
@@ -238,10 +246,10 @@ macro_rules! spi_dmatx {
                 }
             }
         }
-        impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in slave mode with DMA on [Ch]
             pub fn with_tx_dma<DMA: DmaExt, const C: u8>(self, mut channel: Ch<DMA, C>) ->
-                SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>> {
+                SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, C>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 channel.set_map($dmamux);
@@ -255,26 +263,26 @@ macro_rules! spi_dmatx {
 
         $(
             /// Alias for SPI transmit instance in master mode with DMA
-            pub type $txdma<SCKPIN, MISOPIN, MOSIPIN> = SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>;
+            pub type $txdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> = SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>;
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Transmit for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Transmit for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 type TxChannel = $ch;
-                type ReceivedWord = u8;
+                type ReceivedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 /// Release the SPI instance, pins, and DMA channel
-                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $ch) {
+                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $ch) {
                     self.stop();
                     // reset the mux to default
                     self.channel.set_map(PeriphMap::Adc);
                     let SpiTxDma { payload, channel } = self;
-                    payload.spi.cr2.modify(|_, w| w.rxdmaen().clear_bit());
+                    payload.spi.cr2.modify(|_, w| w.txdmaen().clear_bit());
                     (payload, channel)
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 fn start(&mut self) {
                     self.channel.start();
                 }
@@ -283,9 +291,9 @@ macro_rules! spi_dmatx {
                 }
             }
 
-            impl<B, SCKPIN, MISOPIN, MOSIPIN> WriteDma<B, u8> for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>
+            impl<B, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> WriteDma<B, W> for SpiTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>
             where
-                B: ReadBuffer<Word = u8>,
+                B: ReadBuffer<Word = W>,
             {
                 fn write(mut self, buffer: B) -> Transfer<dma::R, B, Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -297,6 +305,7 @@ macro_rules! spi_dmatx {
                     );
                     self.channel.set_memory_address(ptr as u32, true);
                     self.channel.set_transfer_length(len);
+                    self.channel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.channel.ch().cr.modify(|_, w| {
@@ -304,14 +313,10 @@ macro_rules! spi_dmatx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
-                        // write to memory
-                        w.dir().clear_bit()
+                        // read from memory
+                        w.dir().set_bit()
                     });
                     self.start();
 
@@ -320,18 +325,18 @@ macro_rules! spi_dmatx {
             }
 
             /// Alias for SPI instance in slave mode with DMA
-            pub type $slavetxdma<SCKPIN, MISOPIN, MOSIPIN> =
-                SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>;
+            pub type $slavetxdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> =
+                SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>;
 
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Transmit for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Transmit for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 type TxChannel = $ch;
-                type ReceivedWord = u8;
+                type ReceivedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 /// Release the SPI instance, pins and DMA channel
-                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $ch) {
+                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $ch) {
                     self.stop();
                     // reset the mux to default
                     self.channel.set_map(PeriphMap::Adc);
@@ -341,7 +346,7 @@ macro_rules! spi_dmatx {
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W> {
                 fn start(&mut self) {
                     self.channel.start();
                 }
@@ -350,9 +355,9 @@ macro_rules! spi_dmatx {
                 }
             }
 
-            impl<B, SCKPIN, MISOPIN, MOSIPIN> WriteDma<B, u8> for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch>
+            impl<B, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> WriteDma<B, W> for SpiSlaveTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $ch, W>
             where
-                B: ReadBuffer<Word = u8>,
+                B: ReadBuffer<Word = W>,
             {
                 fn write(mut self, buffer: B) -> Transfer<dma::R, B, Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -364,6 +369,7 @@ macro_rules! spi_dmatx {
                     );
                     self.channel.set_memory_address(ptr as u32, true);
                     self.channel.set_transfer_length(len);
+                    self.channel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.channel.ch().cr.modify(|_, w| {
@@ -371,10 +377,6 @@ macro_rules! spi_dmatx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // read from memory
@@ -413,13 +415,13 @@ macro_rules! spi_dmarxtx {
         $SPIi:ty: ($dmarxmux:expr, $dmatxmux:expr, {
             $($rxtxdma:ident, $slaverxtxdma:ident, ($rxch:ty, $txch:ty),)+
         }),) => {
-        impl<SCKPIN, MISOPIN, MOSIPIN> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in master mode with DMA on [Ch]
             pub fn with_rx_tx_dma<DMA: DmaExt, const CRX: u8, const CTX: u8>(
                 self,
                 mut rxchannel: Ch<DMA, CRX>,
                 mut txchannel: Ch<DMA, CTX>,
-            ) -> SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, CRX>, Ch<DMA, CTX>> {
+            ) -> SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, CRX>, Ch<DMA, CTX>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 rxchannel.set_map($dmarxmux);
@@ -435,13 +437,13 @@ macro_rules! spi_dmarxtx {
                 }
             }
         }
-        impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8> {
+        impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W> {
             /// Use SPI instance in slave mode with DMA on [Ch]
             pub fn with_rx_tx_dma<DMA: DmaExt, const CRX: u8, const CTX: u8>(
                 self,
                 mut rxchannel: Ch<DMA, CRX>,
                 mut txchannel: Ch<DMA, CTX>,
-            ) -> SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, CRX>, Ch<DMA, CTX>> {
+            ) -> SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, Ch<DMA, CRX>, Ch<DMA, CTX>, W> {
                 // turn on syscfg clock and set mux
                 unsafe { pac::SYSCFG::enable(&*pac::RCC::ptr()) };
                 rxchannel.set_map($dmarxmux);
@@ -460,21 +462,21 @@ macro_rules! spi_dmarxtx {
 
         $(
             /// Alias for SPI tx/rx instance in master mode with DMA
-            pub type $rxtxdma<SCKPIN, MISOPIN, MOSIPIN> = SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch>;
+            pub type $rxtxdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> = SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W>;
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Transmit for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Transmit for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 type TxChannel = $txch;
-                type ReceivedWord = u8;
+                type ReceivedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Receive for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Receive for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 type RxChannel = $rxch;
-                type TransmittedWord = u8;
+                type TransmittedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 /// Release the SPI instance, pins, and DMA channels
-                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $rxch, $txch) {
+                pub fn release(mut self) -> (Spi<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $rxch, $txch) {
                     self.stop();
                     // reset the mux to default
                     self.rxchannel.set_map(PeriphMap::Adc);
@@ -485,7 +487,7 @@ macro_rules! spi_dmarxtx {
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 fn start(&mut self) {
                     self.rxchannel.start();
                     self.txchannel.start();
@@ -496,10 +498,10 @@ macro_rules! spi_dmarxtx {
                 }
             }
 
-            impl<RXB, TXB, SCKPIN, MISOPIN, MOSIPIN> ReadWriteDma<RXB, TXB, u8> for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch>
+            impl<RXB, TXB, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> ReadWriteDma<RXB, TXB, W> for SpiRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W>
             where
-                RXB: WriteBuffer<Word = u8>,
-                TXB: ReadBuffer<Word = u8>,
+                RXB: WriteBuffer<Word = W>,
+                TXB: ReadBuffer<Word = W>,
             {
                 fn read_write(mut self, mut rxbuffer: RXB, txbuffer: TXB) -> Transfer<dma::W, (RXB, TXB), Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -517,6 +519,7 @@ macro_rules! spi_dmarxtx {
                     );
                     self.rxchannel.set_memory_address(rxptr as u32, true);
                     self.rxchannel.set_transfer_length(rxlen);
+                    self.rxchannel.set_data_size(W::SIZE);
 
                     self.txchannel.set_peripheral_address(
                         unsafe { (*<$SPIi>::ptr()).dr().as_ptr() as u32 },
@@ -524,6 +527,7 @@ macro_rules! spi_dmarxtx {
                     );
                     self.txchannel.set_memory_address(txptr as u32, true);
                     self.txchannel.set_transfer_length(txlen);
+                    self.txchannel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.rxchannel.ch().cr.modify(|_, w| {
@@ -531,10 +535,6 @@ macro_rules! spi_dmarxtx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // write to memory
@@ -545,10 +545,6 @@ macro_rules! spi_dmarxtx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // read from memory
@@ -561,22 +557,22 @@ macro_rules! spi_dmarxtx {
             }
 
             /// Alias for SPI rx/tx instance in slave mode with DMA
-            pub type $slaverxtxdma<SCKPIN, MISOPIN, MOSIPIN> =
-                SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch>;
+            pub type $slaverxtxdma<SCKPIN, MISOPIN, MOSIPIN, W = u8> =
+                SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W>;
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Receive for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Receive for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 type RxChannel = $rxch;
-                type TransmittedWord = u8;
+                type TransmittedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> Transmit for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> Transmit for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 type TxChannel = $txch;
-                type ReceivedWord = u8;
+                type ReceivedWord = W;
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 /// Release the SPI instance, pins, and DMA channels
-                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, u8>, $rxch, $txch) {
+                pub fn release(mut self) -> (SpiSlave<$SPIi, SCKPIN, MISOPIN, MOSIPIN, W>, $rxch, $txch) {
                     self.stop();
                     // reset the mux to default
                     self.rxchannel.set_map(PeriphMap::Adc);
@@ -587,7 +583,7 @@ macro_rules! spi_dmarxtx {
                 }
             }
 
-            impl<SCKPIN, MISOPIN, MOSIPIN> TransferPayload for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch> {
+            impl<SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> TransferPayload for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W> {
                 fn start(&mut self) {
                     self.rxchannel.start();
                     self.txchannel.start();
@@ -598,10 +594,10 @@ macro_rules! spi_dmarxtx {
                 }
             }
 
-            impl<RXB, TXB, SCKPIN, MISOPIN, MOSIPIN> ReadWriteDma<RXB, TXB, u8> for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch>
+            impl<RXB, TXB, SCKPIN, MISOPIN, MOSIPIN, W: DmaFrame> ReadWriteDma<RXB, TXB, W> for SpiSlaveRxTxDma<$SPIi, SCKPIN, MISOPIN, MOSIPIN, $rxch, $txch, W>
             where
-                RXB: WriteBuffer<Word = u8>,
-                TXB: ReadBuffer<Word = u8>,
+                RXB: WriteBuffer<Word = W>,
+                TXB: ReadBuffer<Word = W>,
             {
                 fn read_write(mut self, mut rxbuffer: RXB, txbuffer: TXB) -> Transfer<dma::W, (RXB, TXB), Self> {
                     // NOTE(unsafe) We own the buffer now and we won't call other `&mut` on it
@@ -619,12 +615,14 @@ macro_rules! spi_dmarxtx {
                     );
                     self.rxchannel.set_memory_address(rxptr as u32, true);
                     self.rxchannel.set_transfer_length(rxlen);
+                    self.rxchannel.set_data_size(W::SIZE);
                     self.txchannel.set_peripheral_address(
                         unsafe { (*<$SPIi>::ptr()).dr().as_ptr() as u32 },
                         false,
                     );
                     self.txchannel.set_memory_address(txptr as u32, true);
                     self.txchannel.set_transfer_length(txlen);
+                    self.txchannel.set_data_size(W::SIZE);
 
                     atomic::compiler_fence(Ordering::Release);
                     self.rxchannel.ch().cr.modify(|_, w| {
@@ -632,10 +630,6 @@ macro_rules! spi_dmarxtx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // write to memory
@@ -646,10 +640,6 @@ macro_rules! spi_dmarxtx {
                         w.mem2mem().clear_bit();
                         // medium channel priority level
                         w.pl().medium();
-                        // 8-bit memory size
-                        w.msize().bits8();
-                        // 8-bit peripheral size
-                        w.psize().bits8();
                         // circular mode disabled
                         w.circ().clear_bit();
                         // read from memory
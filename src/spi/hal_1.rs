@@ -33,6 +33,7 @@ impl embedded_hal::spi::Error for Error {
         match self {
             Self::Overrun => ErrorKind::Overrun,
             Self::ModeFault => ErrorKind::ModeFault,
+            Self::Timeout | Self::Dma => ErrorKind::Other,
         }
     }
 }
@@ -64,25 +65,48 @@ mod nb {
 }
 
 mod blocking {
-    use super::super::{Instance, Spi, SpiReadWrite};
+    use super::super::{Error, Instance, Spi, SpiInner, SpiReadWrite};
     use embedded_hal::spi::SpiBus;
 
+    impl<SPI: Instance, W: Copy> SpiInner<SPI, W> {
+        /// Sends a word and returns the word received meanwhile
+        fn transfer_word(&mut self, word: W) -> Result<W, Error> {
+            nb::block!(self.write_nonblocking(word))?;
+            nb::block!(self.read_nonblocking())
+        }
+    }
+
     impl<SPI, SCKPIN, MISOPIN, MOSIPIN, WIDTH> SpiBus<WIDTH>
         for Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, WIDTH>
     where
         SPI: Instance,
-        WIDTH: Copy + 'static,
+        WIDTH: Copy + Default + 'static,
     {
-        fn transfer_in_place(&mut self, _words: &mut [WIDTH]) -> Result<(), Self::Error> {
-            todo!()
+        fn transfer_in_place(&mut self, words: &mut [WIDTH]) -> Result<(), Self::Error> {
+            self.drain_rx();
+            for word in words.iter_mut() {
+                *word = self.transfer_word(*word)?;
+            }
+            Ok(())
         }
 
-        fn transfer(&mut self, _buff: &mut [WIDTH], _data: &[WIDTH]) -> Result<(), Self::Error> {
-            todo!()
+        fn transfer(&mut self, read: &mut [WIDTH], write: &[WIDTH]) -> Result<(), Self::Error> {
+            self.drain_rx();
+            for i in 0..read.len().max(write.len()) {
+                let word = self.transfer_word(write.get(i).copied().unwrap_or_default())?;
+                if let Some(slot) = read.get_mut(i) {
+                    *slot = word;
+                }
+            }
+            Ok(())
         }
 
-        fn read(&mut self, _words: &mut [WIDTH]) -> Result<(), Self::Error> {
-            todo!()
+        fn read(&mut self, words: &mut [WIDTH]) -> Result<(), Self::Error> {
+            self.drain_rx();
+            for word in words.iter_mut() {
+                *word = self.transfer_word(WIDTH::default())?;
+            }
+            Ok(())
         }
 
         fn write(&mut self, words: &[WIDTH]) -> Result<(), Self::Error> {
@@ -90,6 +114,7 @@ mod blocking {
         }
 
         fn flush(&mut self) -> Result<(), Self::Error> {
+            while self.is_busy() {}
             Ok(())
         }
     }
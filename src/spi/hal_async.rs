@@ -0,0 +1,351 @@
+//! Async transfers completing from the SPI and DMA interrupts
+//!
+//! [`Spi`] implements [`embedded_hal_async::spi::SpiBus`] with the SPI interrupt: a waiting
+//! future enables the receive and error interrupts, and [on_interrupt] disables them again and
+//! wakes the task. This works on every part, including the py32f002a and py32f002b which have no
+//! DMA, at the cost of an interrupt per word.
+//!
+//! With DMA, [`SpiRxTxDma`](super::dma::SpiRxTxDma) implements it too: the words are moved by the
+//! DMA channels and the task is woken by [`crate::dma::on_interrupt`] once the receive channel
+//! completes or a channel fails, and by [on_interrupt] on an overrun or mode fault. The
+//! application binds the handlers it uses:
+//!
+//! ```rust
+//! #[interrupt]
+//! fn SPI1() {
+//!     py32f0xx_hal::spi::on_interrupt::<pac::SPI1>();
+//! }
+//!
+//! #[interrupt]
+//! fn DMA_CHANNEL2_3() {
+//!     py32f0xx_hal::dma::on_interrupt();
+//! }
+//! ```
+//!
+//! Dropping a pending transfer stops it, the next transfer drops the words left in the receive
+//! fifo.
+
+use super::{Error, Instance, Spi, SpiInner, SpiReadWrite};
+use crate::waker::WakerSlot;
+
+use core::future::poll_fn;
+use core::task::Poll;
+
+/// Waker of the task waiting on an SPI
+#[doc(hidden)]
+pub struct State {
+    waker: WakerSlot,
+}
+
+impl State {
+    pub(crate) const fn new() -> Self {
+        Self {
+            waker: WakerSlot::new(),
+        }
+    }
+}
+
+/// SPI interrupt handler for async transfers
+///
+/// Disables the receive and error interrupts if they have a pending event, and wakes the waiting
+/// task.
+pub fn on_interrupt<SPI: Instance>() {
+    // NOTE(unsafe) only interrupt enable bits are modified, and the task waiting on them is woken
+    let spi = unsafe { &*SPI::ptr() };
+    let sr = spi.sr.read();
+    let cr2 = spi.cr2.read();
+    let errors = sr.ovr().bit_is_set() || sr.modf().bit_is_set();
+    if (cr2.rxneie().bit_is_set() && sr.rxne().bit_is_set()) || (cr2.errie().bit_is_set() && errors)
+    {
+        spi.cr2
+            .modify(|_, w| w.rxneie().clear_bit().errie().clear_bit());
+        SPI::state().waker.wake();
+    }
+}
+
+impl<SPI: Instance, W: Copy + Default> SpiInner<SPI, W> {
+    /// Transfers `len` words, sending `write` or `buf` if it is `None`, and storing the words
+    /// received into `buf`
+    ///
+    /// Missing words are sent as zero and extra received words are dropped.
+    async fn transfer_async(
+        &mut self,
+        buf: &mut [W],
+        write: Option<&[W]>,
+        len: usize,
+    ) -> Result<(), Error> {
+        self.drain_rx();
+        let mut sent = 0;
+        let mut received = 0;
+        poll_fn(|cx| {
+            loop {
+                let sr = self.spi.sr.read();
+                if sr.ovr().bit_is_set() {
+                    self.drain_rx();
+                    return Poll::Ready(Err(Error::Overrun));
+                }
+                if sr.modf().bit_is_set() {
+                    return Poll::Ready(Err(Error::ModeFault));
+                }
+                if received < sent && sr.rxne().bit_is_set() {
+                    let word = self.read_data_reg();
+                    if let Some(slot) = buf.get_mut(received) {
+                        *slot = word;
+                    }
+                    received += 1;
+                } else if sent < len && sent == received && sr.txe().bit_is_set() {
+                    let word = match write {
+                        Some(write) => write.get(sent).copied().unwrap_or_default(),
+                        None => buf[sent],
+                    };
+                    self.write_data_reg(word);
+                    sent += 1;
+                } else {
+                    break;
+                }
+            }
+            if received == len {
+                return Poll::Ready(Ok(()));
+            }
+            SPI::state().waker.register(cx.waker());
+            cortex_m::interrupt::free(|_| {
+                self.spi
+                    .cr2
+                    .modify(|_, w| w.rxneie().set_bit().errie().set_bit())
+            });
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<SPI, SCKPIN, MISOPIN, MOSIPIN, W> embedded_hal_async::spi::SpiBus<W>
+    for Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, W>
+where
+    SPI: Instance,
+    W: Copy + Default + 'static,
+{
+    async fn read(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        let len = words.len();
+        self.inner.transfer_async(words, Some(&[]), len).await
+    }
+
+    async fn write(&mut self, words: &[W]) -> Result<(), Self::Error> {
+        self.inner
+            .transfer_async(&mut [], Some(words), words.len())
+            .await
+    }
+
+    async fn transfer(&mut self, read: &mut [W], write: &[W]) -> Result<(), Self::Error> {
+        let len = read.len().max(write.len());
+        self.inner.transfer_async(read, Some(write), len).await
+    }
+
+    async fn transfer_in_place(&mut self, words: &mut [W]) -> Result<(), Self::Error> {
+        let len = words.len();
+        self.inner.transfer_async(words, None, len).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        // every word sent has been received, only the last clock edges may remain
+        while self.is_busy() {}
+        Ok(())
+    }
+}
+
+#[cfg(feature = "with-dma")]
+mod dma {
+    use super::super::{Error, Instance, Spi};
+    use crate::dma::{Ch, DmaExt, Event, RxTxDma};
+
+    use core::future::Future;
+    use core::pin::Pin;
+    use core::sync::atomic::{compiler_fence, Ordering};
+    use core::task::{Context, Poll};
+
+    /// Sets up a one shot byte transfer between the data register and `ptr`
+    fn setup<SPI: Instance, DMA: DmaExt, const C: u8>(
+        channel: &mut Ch<DMA, C>,
+        ptr: u32,
+        inc: bool,
+        len: usize,
+        from_memory: bool,
+    ) {
+        channel.set_peripheral_address(unsafe { (*SPI::ptr()).dr().as_ptr() as u32 }, false);
+        channel.set_memory_address(ptr, inc);
+        channel.set_transfer_length(len);
+        channel.ch().cr.modify(|_, w| {
+            w.mem2mem().clear_bit();
+            w.pl().medium();
+            w.msize().bits8();
+            w.psize().bits8();
+            w.circ().clear_bit();
+            w.dir().bit(from_memory)
+        });
+    }
+
+    /// DMA transfer, completing when the receive channel has received the last word, or with the
+    /// first SPI or DMA error
+    struct TransferFuture<'a, SPI: Instance, DMA: DmaExt, const CRX: u8, const CTX: u8> {
+        spi: &'a SPI,
+        rxchannel: &'a mut Ch<DMA, CRX>,
+        txchannel: &'a mut Ch<DMA, CTX>,
+        running: bool,
+    }
+
+    impl<SPI: Instance, DMA: DmaExt, const CRX: u8, const CTX: u8>
+        TransferFuture<'_, SPI, DMA, CRX, CTX>
+    {
+        fn finish(&mut self) {
+            cortex_m::interrupt::free(|_| self.spi.cr2.modify(|_, w| w.errie().clear_bit()));
+            self.rxchannel.unlisten_async();
+            self.txchannel.unlisten_async();
+            self.rxchannel.stop();
+            self.txchannel.stop();
+            self.running = false;
+            compiler_fence(Ordering::Acquire);
+        }
+    }
+
+    impl<SPI: Instance, DMA: DmaExt, const CRX: u8, const CTX: u8> Future
+        for TransferFuture<'_, SPI, DMA, CRX, CTX>
+    {
+        type Output = Result<(), Error>;
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            let sr = self.spi.sr.read();
+            let result = if sr.ovr().bit_is_set() {
+                Err(Error::Overrun)
+            } else if sr.modf().bit_is_set() {
+                Err(Error::ModeFault)
+            } else if self.rxchannel.transfer_error() || self.txchannel.transfer_error() {
+                Err(Error::Dma)
+            } else if !self.rxchannel.in_progress() {
+                Ok(())
+            } else {
+                self.rxchannel.waker().register(cx.waker());
+                self.txchannel.waker().register(cx.waker());
+                SPI::state().waker.register(cx.waker());
+                self.rxchannel.listen_async(Event::TransferComplete);
+                self.rxchannel.listen_async_error();
+                self.txchannel.listen_async_error();
+                cortex_m::interrupt::free(|_| self.spi.cr2.modify(|_, w| w.errie().set_bit()));
+                return Poll::Pending;
+            };
+            self.finish();
+            Poll::Ready(result)
+        }
+    }
+
+    impl<SPI: Instance, DMA: DmaExt, const CRX: u8, const CTX: u8> Drop
+        for TransferFuture<'_, SPI, DMA, CRX, CTX>
+    {
+        fn drop(&mut self) {
+            if self.running {
+                self.finish();
+            }
+        }
+    }
+
+    impl<SPI, SCKPIN, MISOPIN, MOSIPIN, DMA, const CRX: u8, const CTX: u8>
+        RxTxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, Ch<DMA, CRX>, Ch<DMA, CTX>>
+    where
+        SPI: Instance,
+        DMA: DmaExt,
+    {
+        /// Transfers `len` bytes, the addresses are incremented for the buffers only
+        ///
+        /// The transfer stops at the first error, an overrun drains the receive fifo.
+        async fn transfer_dma(
+            &mut self,
+            rx: (*mut u8, bool),
+            tx: (*const u8, bool),
+            len: usize,
+        ) -> Result<(), Error> {
+            let mut done = 0;
+            while done < len {
+                let chunk = (len - done).min(u16::MAX as usize);
+                let rx_ptr = if rx.1 { rx.0.wrapping_add(done) } else { rx.0 };
+                let tx_ptr = if tx.1 { tx.0.wrapping_add(done) } else { tx.0 };
+                self.payload.inner.drain_rx();
+                setup::<SPI, DMA, CRX>(&mut self.rxchannel, rx_ptr as u32, rx.1, chunk, false);
+                setup::<SPI, DMA, CTX>(&mut self.txchannel, tx_ptr as u32, tx.1, chunk, true);
+                compiler_fence(Ordering::Release);
+                // the receive channel must be ready before the first word is sent
+                self.rxchannel.start();
+                self.txchannel.start();
+                let result = TransferFuture {
+                    spi: &self.payload.inner.spi,
+                    rxchannel: &mut self.rxchannel,
+                    txchannel: &mut self.txchannel,
+                    running: true,
+                }
+                .await;
+                if let Err(Error::Overrun) = result {
+                    self.payload.inner.drain_rx();
+                }
+                result?;
+                done += chunk;
+            }
+            Ok(())
+        }
+    }
+
+    impl<SPI, SCKPIN, MISOPIN, MOSIPIN, DMA, const CRX: u8, const CTX: u8>
+        embedded_hal::spi::ErrorType
+        for RxTxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, Ch<DMA, CRX>, Ch<DMA, CTX>>
+    where
+        SPI: Instance,
+        DMA: DmaExt,
+    {
+        type Error = Error;
+    }
+
+    impl<SPI, SCKPIN, MISOPIN, MOSIPIN, DMA, const CRX: u8, const CTX: u8>
+        embedded_hal_async::spi::SpiBus<u8>
+        for RxTxDma<Spi<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, Ch<DMA, CRX>, Ch<DMA, CTX>>
+    where
+        SPI: Instance,
+        DMA: DmaExt,
+    {
+        async fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            let zero = 0u8;
+            let tx = (&zero as *const u8, false);
+            self.transfer_dma((words.as_mut_ptr(), true), tx, words.len())
+                .await
+        }
+
+        async fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            let mut dummy = 0u8;
+            let rx = (&mut dummy as *mut u8, false);
+            self.transfer_dma(rx, (words.as_ptr(), true), words.len())
+                .await
+        }
+
+        async fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            let common = read.len().min(write.len());
+            let (read, read_rest) = read.split_at_mut(common);
+            let (write, write_rest) = write.split_at(common);
+            self.transfer_dma((read.as_mut_ptr(), true), (write.as_ptr(), true), common)
+                .await?;
+            if !read_rest.is_empty() {
+                self.read(read_rest).await?;
+            }
+            if !write_rest.is_empty() {
+                self.write(write_rest).await?;
+            }
+            Ok(())
+        }
+
+        async fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            let ptr = words.as_mut_ptr();
+            self.transfer_dma((ptr, true), (ptr as *const u8, true), words.len())
+                .await
+        }
+
+        async fn flush(&mut self) -> Result<(), Self::Error> {
+            while self.payload.is_busy() {}
+            Ok(())
+        }
+    }
+}
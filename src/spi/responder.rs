@@ -0,0 +1,247 @@
+//! Interrupt-driven slave serving a register map
+//!
+//! A [Responder] answers a master with a register protocol: the first byte after NSS falls is a
+//! command, with the read bit 7 and a 7-bit register address. The following bytes are written to
+//! or read from the [RegisterMap], incrementing the address after each one, until NSS rises.
+//!
+//! [Responder::on_interrupt] runs from the SPI interrupt and [Responder::deselect] from the EXTI
+//! interrupt of the rising NSS edge. The transmit fifo is flushed on the rising edge and a dummy
+//! byte is loaded for the command, so the reply to a read command is loaded by the interrupt of
+//! the command byte: the master has to leave the interrupt latency between the command byte and
+//! the first data byte. Afterwards the replies are kept two bytes ahead, so each interrupt has a
+//! whole byte time, and [RegisterMap::read] may be called for one register past the last one
+//! the master reads.
+//!
+//! With [NssMode::Hardware] the NSS pin, in its alternate function, gates the SPI and the same
+//! pin raises the EXTI interrupt. With [NssMode::Software] NSS is a plain input, and
+//! [Responder::select] must run from the falling edge interrupt before the first clock edge.
+//!
+//! ```rust
+//! static RESPONDER: Mutex<RefCell<Option<Responder<pac::SPI1, Sck, Miso, Mosi, [u8; 16]>>>> =
+//!     Mutex::new(RefCell::new(None));
+//!
+//! let slave = p.SPI1.spi_slave((Some(sck), Some(miso), Some(mosi)), MODE_0.into());
+//! let responder = slave.responder([0; 16], NssMode::Hardware);
+//!
+//! #[interrupt]
+//! fn SPI1() {
+//!     cortex_m::interrupt::free(|cs| {
+//!         if let Some(responder) = RESPONDER.borrow(cs).borrow_mut().as_mut() {
+//!             responder.on_interrupt().ok();
+//!         }
+//!     });
+//! }
+//!
+//! #[interrupt]
+//! fn EXTI4_15() {
+//!     cortex_m::interrupt::free(|cs| {
+//!         nss.clear_interrupt_pending_bit();
+//!         if let Some(responder) = RESPONDER.borrow(cs).borrow_mut().as_mut() {
+//!             responder.deselect();
+//!         }
+//!     });
+//! }
+//! ```
+
+use super::{Error, Instance, SpiReadWrite, SpiSlave};
+use crate::pac::RCC;
+
+/// Read bit of the command byte
+const READ: u8 = 0x80;
+
+/// Registers served by a [Responder]
+pub trait RegisterMap {
+    /// Returns the value of the register at `address`, for the master to read
+    fn read(&mut self, address: u8) -> u8;
+
+    /// Stores `value` written by the master to the register at `address`
+    fn write(&mut self, address: u8, value: u8);
+
+    /// Called when NSS rises, ending a transaction
+    fn end(&mut self) {}
+}
+
+/// Registers in an array, the addresses past its end read 0 and ignore writes
+impl<const N: usize> RegisterMap for [u8; N] {
+    fn read(&mut self, address: u8) -> u8 {
+        self.get(address as usize).copied().unwrap_or(0)
+    }
+
+    fn write(&mut self, address: u8, value: u8) {
+        if let Some(register) = self.get_mut(address as usize) {
+            *register = value;
+        }
+    }
+}
+
+/// Handling of the NSS pin
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NssMode {
+    /// The NSS pin, in its alternate function, selects the slave
+    Hardware,
+    /// The slave is selected by [Responder::select] and [Responder::deselect]
+    Software,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Waiting for the command byte
+    Command,
+    /// Serving reads, `next` is the address of the next reply to load
+    Read { next: u8 },
+    /// Serving writes at `address`
+    Write { address: u8 },
+    /// Ignoring the rest of the transaction after an overrun
+    Discard,
+}
+
+/// Slave answering register reads and writes, see the [module](self) documentation
+pub struct Responder<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN, MAP> {
+    slave: SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>,
+    map: MAP,
+    nss: NssMode,
+    state: State,
+    /// Bytes loaded for transmission and not clocked out yet
+    ahead: u8,
+}
+
+impl<SPI: Instance, SCKPIN, MISOPIN, MOSIPIN> SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8> {
+    /// Serves `map` from the SPI interrupt, selected as set by `nss`
+    pub fn responder<MAP: RegisterMap>(
+        self,
+        map: MAP,
+        nss: NssMode,
+    ) -> Responder<SPI, SCKPIN, MISOPIN, MOSIPIN, MAP> {
+        let mut responder = Responder {
+            slave: self,
+            map,
+            nss,
+            state: State::Command,
+            ahead: 0,
+        };
+        let spi = &responder.slave.inner.spi;
+        spi.cr1.modify(|_, w| w.spe().clear_bit());
+        spi.cr1.modify(|_, w| {
+            w.ssm().bit(nss == NssMode::Software);
+            w.ssi().set_bit()
+        });
+        spi.cr1.modify(|_, w| w.spe().set_bit());
+        responder.flush();
+        responder
+    }
+}
+
+impl<SPI, SCKPIN, MISOPIN, MOSIPIN, MAP> Responder<SPI, SCKPIN, MISOPIN, MOSIPIN, MAP>
+where
+    SPI: Instance,
+    MAP: RegisterMap,
+{
+    /// Handles the received bytes, call it from the SPI interrupt
+    ///
+    /// An overrun drops the rest of the transaction and is reported.
+    pub fn on_interrupt(&mut self) -> Result<(), Error> {
+        let sr = self.slave.inner.spi.sr.read();
+        if sr.ovr().bit_is_set() {
+            self.state = State::Discard;
+            self.slave.inner.drain_rx();
+            return Err(Error::Overrun);
+        }
+        while self.slave.is_rx_not_empty() {
+            let byte = self.slave.inner.read_data_reg();
+            self.ahead = self.ahead.saturating_sub(1);
+            self.state = match self.state {
+                State::Command if byte & READ != 0 => State::Read { next: byte & !READ },
+                State::Command => State::Write {
+                    address: byte & !READ,
+                },
+                State::Read { next } => State::Read { next },
+                State::Write { address } => {
+                    self.map.write(address, byte);
+                    State::Write {
+                        address: (address + 1) & !READ,
+                    }
+                }
+                State::Discard => State::Discard,
+            };
+            self.fill();
+        }
+        Ok(())
+    }
+
+    /// Selects the slave, call it from the falling edge interrupt of NSS with
+    /// [NssMode::Software]
+    pub fn select(&mut self) {
+        if self.nss == NssMode::Software {
+            self.slave.inner.spi.cr1.modify(|_, w| w.ssi().clear_bit());
+        }
+    }
+
+    /// Ends the transaction, call it from the rising edge interrupt of NSS
+    ///
+    /// The transmit fifo is flushed and the map notified with [RegisterMap::end].
+    pub fn deselect(&mut self) {
+        self.flush();
+        self.map.end();
+    }
+
+    /// The register map
+    pub fn map(&self) -> &MAP {
+        &self.map
+    }
+
+    /// The register map, to update it between transactions
+    pub fn map_mut(&mut self) -> &mut MAP {
+        &mut self.map
+    }
+
+    /// Stops serving the map and returns the slave and the map
+    pub fn release(self) -> (SpiSlave<SPI, SCKPIN, MISOPIN, MOSIPIN, u8>, MAP) {
+        let spi = &self.slave.inner.spi;
+        spi.cr2
+            .modify(|_, w| w.rxneie().clear_bit().errie().clear_bit());
+        spi.cr1.modify(|_, w| w.spe().clear_bit());
+        spi.cr1.modify(|_, w| w.ssm().set_bit().ssi().clear_bit());
+        spi.cr1.modify(|_, w| w.spe().set_bit());
+        (self.slave, self.map)
+    }
+
+    /// Loads the replies, two bytes ahead while reading and one otherwise
+    fn fill(&mut self) {
+        let target = match self.state {
+            State::Read { .. } => 2,
+            _ => 1,
+        };
+        while self.ahead < target && self.slave.is_tx_empty() {
+            let byte = match &mut self.state {
+                State::Read { next } => {
+                    let byte = self.map.read(*next);
+                    *next = (*next + 1) & !READ;
+                    byte
+                }
+                _ => 0,
+            };
+            self.slave.inner.write_data_reg(byte);
+            self.ahead += 1;
+        }
+    }
+
+    /// Resets the SPI to drop the bytes left in the fifos, and waits for a new command
+    fn flush(&mut self) {
+        let spi = &self.slave.inner.spi;
+        let cr1 = spi.cr1.read().bits();
+        let cr2 = spi.cr2.read().bits();
+        // the transmit fifo is only emptied by a reset of the peripheral
+        let rcc = unsafe { &(*RCC::ptr()) };
+        SPI::reset(rcc);
+        spi.cr2.write(|w| unsafe { w.bits(cr2) });
+        spi.cr1.write(|w| unsafe { w.bits(cr1) });
+        if self.nss == NssMode::Software {
+            spi.cr1.modify(|_, w| w.ssi().set_bit());
+        }
+        spi.cr2
+            .modify(|_, w| w.rxneie().set_bit().errie().set_bit());
+        self.state = State::Command;
+        self.ahead = 0;
+        self.fill();
+    }
+}
@@ -0,0 +1,94 @@
+//! SPI clock prescaler selection
+//!
+//! The master clock is the peripheral clock divided by a power of two from 2 to 256.
+//! [SpiPrescaler] picks the fastest clock that does not exceed a requested clock, and reports the
+//! clock actually achieved. It has no hardware dependencies.
+//!
+//! ```rust
+//! let prescaler = SpiPrescaler::new(24_000_000, 1_000_000);
+//! assert_eq!(prescaler.br(), 0b100);
+//! assert_eq!(prescaler.divider(), 32);
+//! assert_eq!(prescaler.achieved(), 750_000);
+//! ```
+
+/// `BR` value for a peripheral clock and SPI clock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SpiPrescaler {
+    clock: u32,
+    br: u8,
+}
+
+impl SpiPrescaler {
+    /// Selects the fastest division of the peripheral `clock` that is at most `freq`
+    ///
+    /// A `freq` above half the peripheral clock selects the fastest clock, and a `freq` below
+    /// the slowest clock, zero included, selects the slowest.
+    pub const fn new(clock: u32, freq: u32) -> Self {
+        let mut br = 0b111;
+        if freq != 0 {
+            // smallest divider with clock / divider <= freq
+            let ratio = clock.div_ceil(freq);
+            br = 0;
+            while br < 0b111 && (2 << br) < ratio {
+                br += 1;
+            }
+        }
+        Self { clock, br }
+    }
+
+    /// The `BR` field value
+    pub const fn br(&self) -> u8 {
+        self.br
+    }
+
+    /// The division of the peripheral clock
+    pub const fn divider(&self) -> u32 {
+        2 << self.br
+    }
+
+    /// The SPI clock achieved
+    pub const fn achieved(&self) -> u32 {
+        self.clock / self.divider()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn at_most_freq() {
+        // clock, freq, divider
+        let table = [
+            (24_000_000, 12_000_000, 2),
+            (24_000_000, 8_000_000, 4),
+            (24_000_000, 6_000_000, 4),
+            (24_000_000, 5_000_000, 8),
+            (24_000_000, 1_000_000, 32),
+            (24_000_000, 750_000, 32),
+            (24_000_000, 749_999, 64),
+            (48_000_000, 4_363_636, 16),
+            (48_000_000, 9_600_000, 8),
+            (8_000_000, 1_000_000, 8),
+        ];
+        for (clock, freq, divider) in table {
+            let prescaler = SpiPrescaler::new(clock, freq);
+            assert_eq!(prescaler.divider(), divider, "{clock} Hz, {freq} Hz");
+            assert_eq!(prescaler.divider(), 2 << prescaler.br());
+            assert!(prescaler.achieved() <= freq);
+            // the next faster division would exceed freq
+            assert!(divider == 2 || clock / (divider / 2) > freq);
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert_eq!(SpiPrescaler::new(24_000_000, 48_000_000).br(), 0b000);
+        assert_eq!(SpiPrescaler::new(24_000_000, 24_000_000).divider(), 2);
+        assert_eq!(SpiPrescaler::new(24_000_000, 93_750).divider(), 256);
+        assert_eq!(SpiPrescaler::new(24_000_000, 1_000).divider(), 256);
+        assert_eq!(SpiPrescaler::new(24_000_000, 0).br(), 0b111);
+        assert_eq!(SpiPrescaler::new(24_000_000, 0).achieved(), 93_750);
+    }
+}
@@ -10,8 +10,10 @@ use crate::gpio::{
     gpioa::Parts as GpioAParts, 
     gpiob::Parts as GpioBParts,
 };
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 use crate::gpio::gpiof::Parts as GpioFParts;
+#[cfg(feature = "py32f072")]
+use crate::gpio::gpioc::Parts as GpioCParts;
 
 /// Configuración del reloj del sistema
 pub struct SystemClockConfig {
@@ -38,8 +40,11 @@ pub struct SystemInit {
     pub gpioa: GpioAParts,
     /// GPIO Port B
     pub gpiob: GpioBParts,
-    /// GPIO Port F (solo disponible en py32f030 y py32f003)
-    #[cfg(any(feature = "py32f030", feature = "py32f003"))]
+    /// GPIO Port C (solo disponible en py32f072)
+    #[cfg(feature = "py32f072")]
+    pub gpioc: GpioCParts,
+    /// GPIO Port F (solo disponible en py32f030, py32f003 y py32f072)
+    #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
     pub gpiof: GpioFParts,
 }
 
@@ -87,14 +92,18 @@ impl SystemInit {
         // Inicializar todos los GPIO disponibles
         let gpioa = p.GPIOA.split();
         let gpiob = p.GPIOB.split();
-        #[cfg(any(feature = "py32f030", feature = "py32f003"))]
+        #[cfg(feature = "py32f072")]
+        let gpioc = p.GPIOC.split();
+        #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
         let gpiof = p.GPIOF.split();
 
         SystemInit {
             rcc,
             gpioa,
             gpiob,
-            #[cfg(any(feature = "py32f030", feature = "py32f003"))]
+            #[cfg(feature = "py32f072")]
+            gpioc,
+            #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
             gpiof,
         }
     }
@@ -45,7 +45,9 @@ pub mod delay;
 pub use delay::*;
 pub mod counter;
 pub use counter::*;
+#[cfg(not(feature = "py32f072"))]
 pub mod pwm;
+#[cfg(not(feature = "py32f072"))]
 pub use pwm::*;
 
 mod hal_02;
@@ -343,6 +345,8 @@ mod sealed {
         fn stop_in_debug(&mut self, dbg: &mut DBG, state: bool);
     }
 
+    // only used by the `pwm` module, which needs the pin tables py32f072 lacks
+    #[cfg_attr(feature = "py32f072", allow(dead_code))]
     pub trait WithPwm: General {
         // Number of Channels
         const CH_NUM: u8;
@@ -887,13 +891,13 @@ hal!(
     pac::TIM1: [Timer1, u16, apb_fz2, dbg_timer1_stop, c: (CH4, 3, _aoe), m: tim1, opm: opm,],
 );
 
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 hal!(
     pac::TIM3: [Timer3, u16, apb_fz1, dbg_timer3_stop, c: (CH4, 0), m: tim3, opm: opm,],
     pac::TIM17: [Timer17, u16, apb_fz2, dbg_timer17_stop, c: (CH1, 1, _aoe), opm: opm,],
 );
 
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 hal!(
     pac::TIM16: [Timer16, u16, apb_fz2, dbg_timer16_stop, c: (CH1, 1, _aoe), opm: opm,],
 );
@@ -903,7 +907,20 @@ hal!(
     pac::TIM16: [Timer16, u16, apb_fz2, dbg_timer16_stop, opm: opm,],
 );
 
-#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002b"))]
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002b",
+    feature = "py32f072"
+))]
 hal!(
     pac::TIM14: [Timer14, u16, apb_fz2, dbg_timer14_stop, c: (CH1, 0),],
 );
+
+#[cfg(feature = "py32f072")]
+hal!(
+    pac::TIM2: [Timer2, u16, apb_fz1, dbg_timer2_stop, c: (CH4, 0), m: tim2, opm: opm,],
+    pac::TIM6: [Timer6, u16, apb_fz1, dbg_timer6_stop, m: tim6, opm: opm,],
+    pac::TIM7: [Timer7, u16, apb_fz1, dbg_timer7_stop, m: tim6, opm: opm,],
+    pac::TIM15: [Timer15, u16, apb_fz2, dbg_timer15_stop, m: tim15, opm: opm,],
+);
@@ -11,10 +11,9 @@ use embedded_hal_02::{
 use fugit::{ExtU32, TimerDurationU32};
 use void::Void;
 
-use super::{
-    Channel, Counter, CounterHz, Delay, Error, Instance, OcPin, Pins, PwmChannel, PwmHz,
-    SysCounter, SysCounterHz, SysDelay, WithPwm,
-};
+#[cfg(not(feature = "py32f072"))]
+use super::{Channel, OcPin, Pins, PwmChannel, PwmHz, WithPwm};
+use super::{Counter, CounterHz, Delay, Error, Instance, SysCounter, SysCounterHz, SysDelay};
 
 impl DelayUs<u32> for SysDelay {
     fn delay_us(&mut self, us: u32) {
@@ -134,6 +133,7 @@ impl<const FREQ: u32> Cancel for SysCounter<FREQ> {
     }
 }
 
+#[cfg(not(feature = "py32f072"))]
 impl<TIM: Instance + WithPwm, CH: OcPin> embedded_hal_02::PwmPin for PwmChannel<TIM, CH> {
     type Duty = u16;
 
@@ -154,6 +154,7 @@ impl<TIM: Instance + WithPwm, CH: OcPin> embedded_hal_02::PwmPin for PwmChannel<
     }
 }
 
+#[cfg(not(feature = "py32f072"))]
 impl<TIM, P, PINS> embedded_hal_02::Pwm for PwmHz<TIM, P, PINS>
 where
     TIM: Instance + WithPwm,
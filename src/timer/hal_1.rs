@@ -5,7 +5,10 @@
 
 use embedded_hal::delay::DelayNs;
 
-use super::{Delay, Instance, OcPin, PwmChannel, SysDelay, WithPwm};
+use super::{Delay, Instance, SysDelay};
+#[cfg(not(feature = "py32f072"))]
+use super::{OcPin, PwmChannel, WithPwm};
+#[cfg(not(feature = "py32f072"))]
 use core::convert::Infallible;
 use fugit::ExtU32Ceil;
 
@@ -33,10 +36,12 @@ impl<TIM: Instance, const FREQ: u32> DelayNs for Delay<TIM, FREQ> {
     }
 }
 
+#[cfg(not(feature = "py32f072"))]
 impl<TIM: Instance + WithPwm, CH: OcPin> embedded_hal::pwm::ErrorType for PwmChannel<TIM, CH> {
     type Error = Infallible;
 }
 
+#[cfg(not(feature = "py32f072"))]
 impl<TIM: Instance + WithPwm, CH: OcPin> embedded_hal::pwm::SetDutyCycle for PwmChannel<TIM, CH> {
     fn max_duty_cycle(&self) -> u16 {
         self.get_max_duty()
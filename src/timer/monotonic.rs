@@ -121,7 +121,7 @@ macro_rules! mono {
                     Some(_) => cnt.wrapping_add(0xffff), // Will overflow, run for as long as possible
                 };
 
-                #[cfg(any(feature = "py32f030", feature = "py32f003"))]
+                #[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
                 self.tim.ccr[0].write(|w| unsafe { w.ccr1().bits(val) });
                 #[cfg(any(feature = "py32f002a", feature = "py32f002b"))]
                 self.tim.ccr[0].write(|w| unsafe { w.ccr().bits(val) });
@@ -150,13 +150,21 @@ macro_rules! mono {
 
 mono!(crate::pac::TIM1);
 
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 mono!(crate::pac::TIM3);
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 mono!(crate::pac::TIM17);
 
-#[cfg(any(feature = "py32f030", feature = "py32f003"))]
+#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f072"))]
 mono!(crate::pac::TIM16);
 
-#[cfg(any(feature = "py32f030", feature = "py32f003", feature = "py32f002b"))]
+#[cfg(any(
+    feature = "py32f030",
+    feature = "py32f003",
+    feature = "py32f002b",
+    feature = "py32f072"
+))]
 mono!(crate::pac::TIM14);
+
+#[cfg(feature = "py32f072")]
+mono!(crate::pac::TIM2);
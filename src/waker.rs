@@ -0,0 +1,33 @@
+//! Waker storage shared by interrupt handlers and futures
+
+use core::cell::RefCell;
+use core::task::Waker;
+
+use cortex_m::interrupt::Mutex;
+
+/// Waker of the task waiting for an interrupt
+pub(crate) struct WakerSlot(Mutex<RefCell<Option<Waker>>>);
+
+impl WakerSlot {
+    pub(crate) const fn new() -> Self {
+        Self(Mutex::new(RefCell::new(None)))
+    }
+
+    /// Stores `waker`, unless it wakes the same task as the stored one
+    pub(crate) fn register(&self, waker: &Waker) {
+        cortex_m::interrupt::free(|cs| {
+            let mut slot = self.0.borrow(cs).borrow_mut();
+            match slot.as_ref() {
+                Some(w) if w.will_wake(waker) => {}
+                _ => *slot = Some(waker.clone()),
+            }
+        });
+    }
+
+    /// Wakes the stored waker, if any
+    pub(crate) fn wake(&self) {
+        if let Some(waker) = cortex_m::interrupt::free(|cs| self.0.borrow(cs).borrow_mut().take()) {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,224 @@
+#! /usr/bin/env python3
+"""Generate the alternate function pin tables of the HAL.
+
+The pin to peripheral signal maps live in `tools/af/<variant>.csv`, one file per
+device variant, with the columns `pin,af,peripheral,signal`. The vendor pack in
+`tools/Misc` does not ship the datasheet AF tables, so these files are the
+transcribed source of truth. The pack SVD files are used to validate them: every
+peripheral and GPIO port used in a table must exist on the variant.
+
+The generated code replaces the regions between `// afgen:begin <table>` and
+`// afgen:end <table>` markers in the source files.
+
+Usage:
+    afgen.py          rewrite the generated regions
+    afgen.py --check  exit with an error if a region differs from the data
+"""
+
+import csv
+import difflib
+import os
+import re
+import sys
+import xml.etree.ElementTree as ET
+
+ROOT = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+DATA_DIR = os.path.join(ROOT, "tools", "af")
+
+# Variant feature -> SVD file, in the order used for `cfg` attributes
+VARIANTS = [
+    ("py32f030", "tools/Misc/SVD/py32f030xx.svd"),
+    ("py32f003", "tools/Misc/SVD/py32f003xx.svd"),
+    ("py32f002a", "tools/Misc/SVD/py32f002axx.svd"),
+    ("py32f002b", "tools/Misc/PuyaPack/SVD/py32f002bxx.svd"),
+    ("py32f072", "tools/Misc/SVD/py32f072xx.svd"),
+]
+
+# Table name -> (source file, peripheral name pattern, signal -> trait)
+TABLES = {
+    "serial": (
+        "src/serial.rs",
+        r"USART\d",
+        {"TX": "TxPin", "RX": "RxPin", "CK": "CkPin", "RTS": "RtsPin", "CTS": "CtsPin"},
+    ),
+    "spi": (
+        "src/spi.rs",
+        r"SPI\d",
+        {"SCK": "SckPin", "MISO": "MisoPin", "MOSI": "MosiPin"},
+    ),
+    "i2c": (
+        "src/i2c.rs",
+        r"I2C\d?",
+        {"SCL": "SclPin", "SDA": "SdaPin"},
+    ),
+    "timer": (
+        "src/timer/pins.rs",
+        r"TIM\d+",
+        {
+            "CH1": "PinC1",
+            "CH1N": "PinC1N",
+            "CH2": "PinC2",
+            "CH2N": "PinC2N",
+            "CH3": "PinC3",
+            "CH3N": "PinC3N",
+            "CH4": "PinC4",
+        },
+    ),
+}
+
+MAX_WIDTH = 100
+
+
+class DataError(Exception):
+    pass
+
+
+def svd_peripherals(path):
+    root = ET.parse(os.path.join(ROOT, path)).getroot()
+    return {p.findtext("name") for p in root.iter("peripheral")}
+
+
+def pin_key(pin):
+    return (pin[1], int(pin[2:]))
+
+
+def load():
+    """Returns {(pin, af, peripheral, signal): set(variants)}"""
+    rows = {}
+    for variant, svd in VARIANTS:
+        path = os.path.join(DATA_DIR, variant + ".csv")
+        if not os.path.exists(path):
+            # the variant is in scope of the HAL but its AF table is not transcribed yet
+            print("note: no {}, {} pins get no pin trait implementations".format(
+                os.path.relpath(path, ROOT), variant))
+            continue
+        peripherals = svd_peripherals(svd)
+        with open(path, newline="") as f:
+            for n, row in enumerate(csv.DictReader(f), start=2):
+                where = "{}:{}".format(os.path.relpath(path, ROOT), n)
+                pin = row["pin"].strip()
+                af = int(row["af"])
+                peripheral = row["peripheral"].strip()
+                signal = row["signal"].strip()
+                if not re.fullmatch(r"P[A-F]\d{1,2}", pin) or int(pin[2:]) > 15:
+                    raise DataError("{}: bad pin {}".format(where, pin))
+                if not 0 <= af <= 15:
+                    raise DataError("{}: bad alternate function {}".format(where, af))
+                if "GPIO" + pin[1] not in peripherals:
+                    raise DataError("{}: {} has no port {}".format(where, variant, pin[1]))
+                if peripheral not in peripherals:
+                    raise DataError("{}: {} has no {}".format(where, variant, peripheral))
+                key = (pin, af, peripheral, signal)
+                if variant in rows.setdefault(key, set()):
+                    raise DataError("{}: duplicate row".format(where))
+                rows[key].add(variant)
+    return rows
+
+
+def cfg(variants):
+    order = [v for v, _ in VARIANTS if v in variants]
+    if len(order) == 1:
+        return '#[cfg(feature = "{}")]'.format(order[0])
+    features = ['feature = "{}"'.format(v) for v in order]
+    if len(", ".join(features)) <= 70:
+        return "#[cfg(any({}))]".format(", ".join(features))
+    # rustfmt splits the attribute arguments longer than `attr_fn_like_width`
+    return "#[cfg(any(\n{}\n))]".format(",\n".join("    " + f for f in features))
+
+
+def wrap_list(indent, head, items):
+    """`head [a, b, c],` on one line, or one line per item when too long"""
+    line = "{}{}[{}],".format(indent, head, ", ".join(items))
+    if len(line) <= MAX_WIDTH:
+        return [line]
+    lines = ["{}{}[".format(indent, head)]
+    lines += ["{}    {},".format(indent, item) for item in items]
+    lines.append("{}],".format(indent))
+    return lines
+
+
+def generate(table, rows):
+    _, pattern, signals = TABLES[table]
+    variant_order = [v for v, _ in VARIANTS]
+    groups = {}
+    for (pin, af, peripheral, signal), variants in rows.items():
+        if not re.fullmatch(pattern, peripheral):
+            continue
+        if signal not in signals:
+            raise DataError("unknown {} signal {} on {}".format(table, signal, pin))
+        groups.setdefault(frozenset(variants), []).append((pin, af, peripheral, signal))
+
+    def group_key(variants):
+        return (-len(variants), sorted(variant_order.index(v) for v in variants))
+
+    out = ["// Generated by tools/afgen.py from tools/af/*.csv, do not edit by hand"]
+    for variants in sorted(groups, key=group_key):
+        entries = groups[variants]
+        out.append("")
+        out.append(cfg(variants))
+        out.append("af_pins! {")
+        instances = sorted({e[2] for e in entries}, key=lambda p: (len(p), p))
+        for instance in instances:
+            out.append("    {} => {{".format(instance))
+            for signal, trait in signals.items():
+                pins = sorted(
+                    (e for e in entries if e[2] == instance and e[3] == signal),
+                    key=lambda e: (pin_key(e[0]), e[1]),
+                )
+                if pins:
+                    items = ["{}<AF{}>".format(pin, af) for pin, af, _, _ in pins]
+                    out += wrap_list("        ", "{} => ".format(trait), items)
+            out.append("    }")
+        out.append("}")
+    return out
+
+
+def regions(text, table):
+    begin = "// afgen:begin {}\n".format(table)
+    end = "// afgen:end {}\n".format(table)
+    start = text.find(begin)
+    stop = text.find(end)
+    if start < 0 or stop < start:
+        raise DataError("missing afgen markers for table {}".format(table))
+    return start + len(begin), stop
+
+
+def main():
+    check = "--check" in sys.argv
+    try:
+        rows = load()
+        failed = False
+        for table, (path, _, _) in TABLES.items():
+            full = os.path.join(ROOT, path)
+            with open(full) as f:
+                text = f.read()
+            start, stop = regions(text, table)
+            generated = "\n".join(generate(table, rows)) + "\n"
+            current = text[start:stop]
+            if current == generated:
+                continue
+            if check:
+                failed = True
+                print("{} disagrees with tools/af data:".format(path))
+                sys.stdout.writelines(
+                    difflib.unified_diff(
+                        current.splitlines(True),
+                        generated.splitlines(True),
+                        path,
+                        "generated",
+                    )
+                )
+            else:
+                with open(full, "w") as f:
+                    f.write(text[:start] + generated + text[stop:])
+                print("Updated {}".format(path))
+    except DataError as e:
+        print("error: {}".format(e))
+        sys.exit(2)
+    if failed:
+        print("\nRun tools/afgen.py to regenerate, or fix tools/af/*.csv")
+        sys.exit(1)
+
+
+if __name__ == "__main__":
+    main()
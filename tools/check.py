@@ -39,10 +39,14 @@ def main():
         and x != "py32f003"
         and x != "py32f002a"
         and x != "py32f002b"
+        and x != "py32f072"
         and not x.startswith("flash")
         and not x.startswith("ram")
     ]
 
+    if not run_inner([sys.executable, "tools/afgen.py", "--check"]):
+        sys.exit(-1)
+
     if 'size_check' in sys.argv:
         cargo_cmd = ['cargo', 'build', '--release']
     else: